@@ -3,10 +3,24 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
+use geodata_anchor::msg::AnchorsInBoundingBoxResponse;
+use geodata_anchor::msg::ChallengeResponse;
+use geodata_anchor::msg::ChallengesResponse;
+use geodata_anchor::msg::ConfigResponse;
 use geodata_anchor::msg::DetailsResponse;
 use geodata_anchor::msg::ExecuteMsg;
+use geodata_anchor::msg::HistoryResponse;
+use geodata_anchor::msg::InclusionResponse;
 use geodata_anchor::msg::InstantiateMsg;
+use geodata_anchor::msg::ListAnchorsResponse;
+use geodata_anchor::msg::MigrateMsg;
+use geodata_anchor::msg::PublicKeyResponse;
 use geodata_anchor::msg::QueryMsg;
+use geodata_anchor::msg::RejectionsResponse;
+use geodata_anchor::msg::StatusResponse;
+use geodata_anchor::msg::ValidResponse;
+use geodata_anchor::msg::ValidationsResponse;
+use geodata_anchor::msg::ValidatorsResponse;
 
 fn main() {
     let mut out_dir = current_dir().unwrap();
@@ -17,5 +31,19 @@ fn main() {
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(MigrateMsg), &out_dir);
     export_schema(&schema_for!(DetailsResponse), &out_dir);
+    export_schema(&schema_for!(ValidResponse), &out_dir);
+    export_schema(&schema_for!(ValidationsResponse), &out_dir);
+    export_schema(&schema_for!(RejectionsResponse), &out_dir);
+    export_schema(&schema_for!(ListAnchorsResponse), &out_dir);
+    export_schema(&schema_for!(StatusResponse), &out_dir);
+    export_schema(&schema_for!(ValidatorsResponse), &out_dir);
+    export_schema(&schema_for!(PublicKeyResponse), &out_dir);
+    export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema(&schema_for!(ChallengeResponse), &out_dir);
+    export_schema(&schema_for!(ChallengesResponse), &out_dir);
+    export_schema(&schema_for!(HistoryResponse), &out_dir);
+    export_schema(&schema_for!(InclusionResponse), &out_dir);
+    export_schema(&schema_for!(AnchorsInBoundingBoxResponse), &out_dir);
 }
@@ -0,0 +1,107 @@
+use crate::error::ContractError;
+use crate::hash::{decode_multibase, parse_multihash, read_varint, HashAlgorithm};
+
+/// IPLD codecs accepted in a CIDv1
+const CODECS: [u64; 4] = [
+    0x55,   // raw
+    0x70,   // dag-pb
+    0x71,   // dag-cbor
+    0x0129, // dag-json
+];
+
+/// Parses a CIDv0 (`Qm...`) or a CIDv1 in base32 (`b`), base58btc (`z`) or base16 (`f`)
+/// multibase, checking its version, codec and multihash.
+/// Returns the binary CIDv1, the same for every encoding of a CID.
+pub fn parse_cid(cid: &str) -> Result<Vec<u8>, ContractError> {
+    let invalid = |reason: &str| ContractError::InvalidCid(reason.to_string());
+
+    // CIDv0 is the base58btc sha2-256 multihash of a dag-pb node
+    if cid.len() == 46 && cid.starts_with("Qm") {
+        let multihash = bs58::decode(cid)
+            .into_vec()
+            .map_err(|_| invalid("not base58btc"))?;
+        let (algorithm, _) = parse_multihash(&multihash).map_err(|_| invalid("bad multihash"))?;
+        if algorithm != HashAlgorithm::Sha2_256 {
+            return Err(invalid("bad multihash"));
+        }
+        let mut bin = vec![0x01, 0x70];
+        bin.extend(multihash);
+        return Ok(bin);
+    }
+
+    if !cid.starts_with(['b', 'z', 'f']) {
+        return Err(invalid("unsupported multibase"));
+    }
+    let bin = decode_multibase(cid).ok_or_else(|| invalid("bad multibase encoding"))?;
+
+    match read_varint(&bin) {
+        Some((1, rest)) => {
+            let (codec, multihash) = read_varint(rest).ok_or_else(|| invalid("bad codec"))?;
+            if !CODECS.contains(&codec) {
+                return Err(invalid("unsupported codec"));
+            }
+            parse_multihash(multihash).map_err(|_| invalid("bad multihash"))?;
+            Ok(bin)
+        }
+        _ => Err(invalid("unsupported version")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::encode_base32;
+
+    #[test]
+    fn test_parse_cid() {
+        let cid = parse_cid("bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi").unwrap();
+        assert_eq!(cid[..4], [0x01, 0x70, 0x12, 0x20]);
+
+        // every encoding of the CID parses the same
+        let multihash: Vec<u8> = [0x12, 0x20].iter().chain(&[5u8; 32]).copied().collect();
+        let v1: Vec<u8> = [0x01, 0x70].iter().chain(&multihash).copied().collect();
+        let v0 = bs58::encode(&multihash).into_string();
+        assert!(v0.starts_with("Qm"));
+        assert_eq!(parse_cid(&v0).unwrap(), v1);
+        assert_eq!(parse_cid(&format!("b{}", encode_base32(&v1))).unwrap(), v1);
+        assert_eq!(
+            parse_cid(&format!("z{}", bs58::encode(&v1).into_string())).unwrap(),
+            v1
+        );
+        assert_eq!(parse_cid(&format!("f{}", hex::encode(&v1))).unwrap(), v1);
+    }
+
+    #[test]
+    fn test_invalid_cid() {
+        let invalid = |reason: &str| Err(ContractError::InvalidCid(reason.to_string()));
+        let multihash: Vec<u8> = [0x12, 0x20].iter().chain(&[5u8; 32]).copied().collect();
+        let cid = |prefix: &[u8], multihash: &[u8]| {
+            let bin: Vec<u8> = prefix.iter().chain(multihash).copied().collect();
+            format!("b{}", encode_base32(&bin))
+        };
+
+        assert_eq!(parse_cid("mAXASIA"), invalid("unsupported multibase"));
+        assert_eq!(parse_cid("bafy!"), invalid("bad multibase encoding"));
+        assert_eq!(
+            parse_cid(&cid(&[0x02, 0x70], &multihash)),
+            invalid("unsupported version")
+        );
+        assert_eq!(
+            parse_cid(&cid(&[0x01, 0x72], &multihash)),
+            invalid("unsupported codec")
+        );
+        // dag-pb as a non-minimal varint would anchor the CID twice
+        assert_eq!(
+            parse_cid(&cid(&[0x01, 0xf0, 0x00], &multihash)),
+            invalid("bad codec")
+        );
+        assert_eq!(
+            parse_cid(&cid(&[0x81, 0x00, 0x70], &multihash)),
+            invalid("unsupported version")
+        );
+        assert_eq!(
+            parse_cid(&cid(&[0x01, 0x55], &multihash[..20])),
+            invalid("bad multihash")
+        );
+    }
+}
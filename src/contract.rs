@@ -1,30 +1,163 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
-use cw2::set_contract_version;
+use cosmwasm_std::{
+    attr, to_binary, Addr, Api, Attribute, BankMsg, Binary, Coin, Deps, DepsMut, Empty, Env, Event,
+    MessageInfo, Order, QuerierWrapper, Response, StdError, StdResult, Storage, Timestamp,
+};
+use cw2::{get_contract_version, set_contract_version};
+use cw4::Cw4Contract;
+use cw_storage_plus::Bound;
+use semver::Version;
+use sha2::{Digest, Sha256};
 
+use crate::cid::parse_cid;
 use crate::error::ContractError;
+use crate::geo::{ancestor_cells, covering_cells, geohash_bounds, BoundingBox};
+use crate::hash::{encode_hash_as, parse_encoded_hash, HashAlgorithm, HashEncoding};
+use crate::merkle::{compute_root, max_proof_len};
 use crate::msg::{
-    is_valid_id, CreateMsg, DetailsResponse, ExecuteMsg, InstantiateMsg, QueryMsg, ValidateMsg,
+    is_valid_id, key_registration_message, validation_message, AnchorSummary,
+    AnchorsInBoundingBoxResponse, ChallengeResponse, ChallengesResponse, ConfigResponse, CreateMsg,
+    DetailsResponse, ExecuteMsg, HistoryResponse, InclusionResponse, InstantiateMsg,
+    ListAnchorsResponse, MigrateMsg, PublicKeyResponse, QueryMsg, RejectionInfo,
+    RejectionsResponse, ReviseMsg, RevisionInfo, StatusResponse, ValidResponse, ValidateMsg,
+    ValidationDispute, ValidationInfo, ValidationsResponse, ValidatorsResponse,
+};
+use crate::state::{
+    add_challenge, add_rejection, add_validation, anchors, anchors_in_geohash, current_tally,
+    geohash_key, latest_challenge, latest_challenge_against, migrate_anchors, rejection_count,
+    tally_validation, untally_validation, validation_count, validation_dispute, Anchor, AnchorKind,
+    AnchorStatus, Challenge, Config, DisputeState, KeyAlgorithm, Migration, PublicKey, Rejection,
+    RejectionReason, Revision, Revocation, Validation, ValidationSignature, ANCHOR_CHALLENGES,
+    CHALLENGES, CHALLENGE_VOTES, CIDS, CONFIG, GEOHASHES, MIGRATION, PUBLIC_KEYS, REJECTIONS,
+    REVISIONS, VALIDATED, VALIDATIONS, VALIDATORS,
 };
-use crate::state::{Anchor, Validation, ANCHORS};
 
 // Version info, for migration info
 const CONTRACT_NAME: &str = "crates.io:geodata-anchor";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+// Versions introducing a state migration, run when migrating from an older one
+const VALIDATIONS_MAP_VERSION: &str = "0.2.0";
+const ANCHOR_INDEXES_VERSION: &str = "0.2.0";
+
+// Pagination defaults for list queries
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+// Geohash index entries a bounding box page scans, in or out of the box
+const MAX_SCANNED: usize = 100;
+
+// Batch size when not configured
+const DEFAULT_MAX_BATCH_SIZE: u32 = 50;
+
+// Anchors migrated per message
+const DEFAULT_MIGRATION_LIMIT: u32 = 100;
+const MAX_MIGRATION_LIMIT: u32 = 200;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
     _env: Env,
     _info: MessageInfo,
-    _msg: InstantiateMsg,
+    msg: InstantiateMsg,
 ) -> StdResult<Response> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
-    // No setup
+    let config = Config {
+        admins: map_validate(deps.api, &msg.admins)?,
+        users: map_validate(deps.api, &msg.users)?,
+        mutable: msg.mutable,
+        ..Default::default()
+    };
+    CONFIG.save(deps.storage, &config)?;
     Ok(Response::default())
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::CannotMigrate {
+            previous_contract: stored.contract,
+        });
+    }
+    let previous: Version = stored.version.parse()?;
+    let current: Version = CONTRACT_VERSION.parse()?;
+    if previous > current {
+        return Err(ContractError::CannotMigrateVersion {
+            previous_version: stored.version,
+        });
+    }
+
+    // state migrations, each one is safe to run again
+    // but only needed from before its version
+    match msg.config {
+        Some(msg) => {
+            let config = Config {
+                admins: map_validate(deps.api, &msg.admins)?,
+                users: map_validate(deps.api, &msg.users)?,
+                mutable: msg.mutable,
+                ..CONFIG.may_load(deps.storage)?.unwrap_or_default()
+            };
+            CONFIG.save(deps.storage, &config)?;
+        }
+        None => {
+            if CONFIG.may_load(deps.storage)?.is_none() {
+                return Err(StdError::not_found("geodata_anchor::state::Config").into());
+            }
+        }
+    }
+    let move_validations = previous < VALIDATIONS_MAP_VERSION.parse()?;
+    let reindex = previous < ANCHOR_INDEXES_VERSION.parse()?;
+    if move_validations || reindex {
+        // a page at a time, the registry may not fit in a block
+        let migration = Migration {
+            start_after: None,
+            move_validations,
+            reindex,
+            time: env.block.time,
+        };
+        MIGRATION.save(deps.storage, &migration)?;
+    }
+    let attributes = migrate_page(deps.storage, msg.limit)?;
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    let res = Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", previous.to_string())
+        .add_attribute("to_version", current.to_string())
+        .add_attributes(attributes);
+    Ok(res)
+}
+
+/// Migrates a page of anchors of the pending migration,
+/// returns the attributes describing it
+fn migrate_page(storage: &mut dyn Storage, limit: Option<u32>) -> StdResult<Vec<Attribute>> {
+    let limit = limit
+        .unwrap_or(DEFAULT_MIGRATION_LIMIT)
+        .min(MAX_MIGRATION_LIMIT) as usize;
+    let (moved, migrated) = migrate_anchors(storage, limit)?;
+    let pending = MIGRATION.may_load(storage)?.is_some();
+    Ok(vec![
+        attr("validations_moved", moved.to_string()),
+        attr("migrated", migrated.to_string()),
+        attr("pending", pending.to_string()),
+    ])
+}
+
+fn map_validate(api: &dyn Api, addresses: &[String]) -> StdResult<Vec<Addr>> {
+    addresses
+        .iter()
+        .map(|addr| api.addr_validate(addr))
+        .collect()
+}
+
+fn load_executable_config(deps: &DepsMut, sender: &Addr) -> Result<Config, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if !config.can_execute(sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(config)
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
@@ -32,10 +165,274 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // anchors saved before their migration would lose their inline validations
+    if !matches!(msg, ExecuteMsg::MigrateAnchors { .. })
+        && MIGRATION.may_load(deps.storage)?.is_some()
+    {
+        return Err(ContractError::MigrationPending {});
+    }
     match msg {
         ExecuteMsg::Create(msg) => execute_create(deps, env, info, msg),
         ExecuteMsg::Validate(msg) => execute_validate(deps, env, info, msg),
+        ExecuteMsg::BatchCreate(msgs) => execute_batch_create(deps, env, info, msgs),
+        ExecuteMsg::BatchValidate(msgs) => execute_batch_validate(deps, env, info, msgs),
+        ExecuteMsg::Reject {
+            id,
+            hash,
+            encoding,
+            reason,
+        } => execute_reject(deps, env, info, id, hash, encoding, reason),
+        ExecuteMsg::Revise(msg) => execute_revise(deps, env, info, msg),
+        ExecuteMsg::Revoke { id, reason } => execute_revoke(deps, env, info, id, reason),
+        ExecuteMsg::Challenge {
+            id,
+            validation,
+            reason,
+        } => execute_challenge(deps, env, info, id, validation, reason),
+        ExecuteMsg::ResolveChallenge {
+            challenge_id,
+            overturn,
+        } => execute_resolve_challenge(deps, env, info, challenge_id, overturn),
+        ExecuteMsg::VoteChallenge {
+            challenge_id,
+            overturn,
+        } => execute_vote_challenge(deps, env, info, challenge_id, overturn),
+        ExecuteMsg::UpdateAdmins { admins } => execute_update_admins(deps, env, info, admins),
+        ExecuteMsg::UpdateUsers { users } => execute_update_users(deps, env, info, users),
+        ExecuteMsg::RegisterKey {
+            algorithm,
+            key,
+            signature,
+        } => execute_register_key(deps, env, info, algorithm, key, signature),
+        ExecuteMsg::SetRevalidationInterval { interval } => {
+            execute_set_revalidation_interval(deps, env, info, interval)
+        }
+        ExecuteMsg::UpdateValidators { add, remove } => {
+            execute_update_validators(deps, env, info, add, remove)
+        }
+        ExecuteMsg::SetQuorum { quorum } => execute_set_quorum(deps, env, info, quorum),
+        ExecuteMsg::SetGroup { group } => execute_set_group(deps, env, info, group),
+        ExecuteMsg::SetMaxBatchSize { size } => execute_set_max_batch_size(deps, env, info, size),
+        ExecuteMsg::SetChallengeBond { bond, treasury } => {
+            execute_set_challenge_bond(deps, env, info, bond, treasury)
+        }
+        ExecuteMsg::Freeze {} => execute_freeze(deps, env, info),
+        ExecuteMsg::MigrateAnchors { limit } => execute_migrate_anchors(deps, env, info, limit),
+    }
+}
+
+pub fn execute_migrate_anchors(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let attributes = migrate_page(deps.storage, limit)?;
+
+    let res = Response::new()
+        .add_attribute("action", "migrate_anchors")
+        .add_attributes(attributes);
+    Ok(res)
+}
+
+fn load_modifiable_config(deps: &DepsMut, sender: &Addr) -> Result<Config, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if !config.can_modify(sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(config)
+}
+
+pub fn execute_update_admins(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    admins: Vec<String>,
+) -> Result<Response, ContractError> {
+    let mut config = load_modifiable_config(&deps, &info.sender)?;
+    config.admins = map_validate(deps.api, &admins)?;
+    CONFIG.save(deps.storage, &config)?;
+
+    let res = Response::new().add_attribute("action", "update_admins");
+    Ok(res)
+}
+
+pub fn execute_update_users(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    users: Vec<String>,
+) -> Result<Response, ContractError> {
+    let mut config = load_modifiable_config(&deps, &info.sender)?;
+    config.users = map_validate(deps.api, &users)?;
+    CONFIG.save(deps.storage, &config)?;
+
+    let res = Response::new().add_attribute("action", "update_users");
+    Ok(res)
+}
+
+pub fn execute_set_revalidation_interval(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    interval: Option<u64>,
+) -> Result<Response, ContractError> {
+    let mut config = load_modifiable_config(&deps, &info.sender)?;
+    config.revalidation_interval = interval;
+    CONFIG.save(deps.storage, &config)?;
+
+    let interval = interval.map_or_else(|| "none".to_string(), |i| i.to_string());
+    let res = Response::new()
+        .add_attribute("action", "set_revalidation_interval")
+        .add_attribute("interval", interval);
+    Ok(res)
+}
+
+pub fn execute_update_validators(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    load_modifiable_config(&deps, &info.sender)?;
+    for validator in map_validate(deps.api, &add)? {
+        VALIDATORS.save(deps.storage, &validator, &Empty {})?;
+    }
+    for validator in map_validate(deps.api, &remove)? {
+        VALIDATORS.remove(deps.storage, &validator);
+    }
+
+    let res = Response::new()
+        .add_attribute("action", "update_validators")
+        .add_attribute("added", add.len().to_string())
+        .add_attribute("removed", remove.len().to_string());
+    Ok(res)
+}
+
+pub fn execute_set_quorum(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    quorum: Option<u64>,
+) -> Result<Response, ContractError> {
+    let mut config = load_modifiable_config(&deps, &info.sender)?;
+    if quorum == Some(0) {
+        return Err(ContractError::InvalidQuorum {});
+    }
+    config.quorum = quorum;
+    CONFIG.save(deps.storage, &config)?;
+
+    let quorum = quorum.map_or_else(|| "none".to_string(), |q| q.to_string());
+    let res = Response::new()
+        .add_attribute("action", "set_quorum")
+        .add_attribute("quorum", quorum);
+    Ok(res)
+}
+
+pub fn execute_set_group(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    group: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut config = load_modifiable_config(&deps, &info.sender)?;
+    config.group = group.map(|g| deps.api.addr_validate(&g)).transpose()?;
+    CONFIG.save(deps.storage, &config)?;
+
+    let group = config
+        .group
+        .map_or_else(|| "none".to_string(), String::from);
+    let res = Response::new()
+        .add_attribute("action", "set_group")
+        .add_attribute("group", group);
+    Ok(res)
+}
+
+pub fn execute_set_max_batch_size(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    size: Option<u32>,
+) -> Result<Response, ContractError> {
+    let mut config = load_modifiable_config(&deps, &info.sender)?;
+    if size == Some(0) {
+        return Err(ContractError::InvalidBatchSize {});
+    }
+    config.max_batch_size = size;
+    CONFIG.save(deps.storage, &config)?;
+
+    let size = size.unwrap_or(DEFAULT_MAX_BATCH_SIZE).to_string();
+    let res = Response::new()
+        .add_attribute("action", "set_max_batch_size")
+        .add_attribute("size", size);
+    Ok(res)
+}
+
+pub fn execute_set_challenge_bond(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    bond: Option<Coin>,
+    treasury: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut config = load_modifiable_config(&deps, &info.sender)?;
+    if let Some(bond) = &bond {
+        // zero amounts can't be sent
+        if bond.amount.is_zero() {
+            return Err(ContractError::InvalidBond {});
+        }
+        if treasury.is_none() {
+            return Err(ContractError::TreasuryRequired {});
+        }
     }
+    config.challenge_bond = bond;
+    config.treasury = treasury.map(|t| deps.api.addr_validate(&t)).transpose()?;
+    CONFIG.save(deps.storage, &config)?;
+
+    let bond = config
+        .challenge_bond
+        .map_or_else(|| "none".to_string(), |b| b.to_string());
+    let treasury = config
+        .treasury
+        .map_or_else(|| "none".to_string(), String::from);
+    let res = Response::new()
+        .add_attribute("action", "set_challenge_bond")
+        .add_attribute("bond", bond)
+        .add_attribute("treasury", treasury);
+    Ok(res)
+}
+
+/// Weight of a validator: its cw4 group weight if configured,
+/// otherwise 1 for registered validators.
+/// A failing group query counts as no weight.
+fn validator_weight(
+    storage: &dyn Storage,
+    querier: &QuerierWrapper,
+    config: &Config,
+    addr: &Addr,
+) -> u64 {
+    match &config.group {
+        Some(group) => Cw4Contract::new(group.clone())
+            .is_member(querier, addr, None)
+            .ok()
+            .flatten()
+            .unwrap_or_default(),
+        None => VALIDATORS.has(storage, addr) as u64,
+    }
+}
+
+pub fn execute_freeze(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut config = load_modifiable_config(&deps, &info.sender)?;
+    config.mutable = false;
+    CONFIG.save(deps.storage, &config)?;
+
+    let res = Response::new().add_attribute("action", "freeze");
+    Ok(res)
 }
 
 pub fn execute_create(
@@ -44,380 +441,4569 @@ pub fn execute_create(
     info: MessageInfo,
     msg: CreateMsg,
 ) -> Result<Response, ContractError> {
+    load_executable_config(&deps, &info.sender)?;
+    let attributes = create_anchor(deps.storage, &info.sender, msg)?;
+
+    let res = Response::new()
+        .add_attribute("action", "create")
+        .add_attributes(attributes);
+    Ok(res)
+}
+
+pub fn execute_batch_create(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msgs: Vec<CreateMsg>,
+) -> Result<Response, ContractError> {
+    let config = load_executable_config(&deps, &info.sender)?;
+    check_batch_size(&config, msgs.len())?;
+
+    // any failure reverts the whole batch
+    let mut res = Response::new()
+        .add_attribute("action", "batch_create")
+        .add_attribute("count", msgs.len().to_string());
+    for msg in msgs {
+        let attributes = create_anchor(deps.storage, &info.sender, msg)?;
+        res = res.add_event(Event::new("create").add_attributes(attributes));
+    }
+    Ok(res)
+}
+
+fn check_batch_size(config: &Config, size: usize) -> Result<(), ContractError> {
+    let max = config.max_batch_size.unwrap_or(DEFAULT_MAX_BATCH_SIZE);
+    if size == 0 {
+        return Err(ContractError::EmptyBatch {});
+    }
+    if size > max as usize {
+        return Err(ContractError::BatchTooLarge { size, max });
+    }
+    Ok(())
+}
+
+/// Stores a new anchor, returns the attributes describing it
+fn create_anchor(
+    storage: &mut dyn Storage,
+    sender: &Addr,
+    msg: CreateMsg,
+) -> Result<Vec<Attribute>, ContractError> {
     if !is_valid_id(&msg.id) {
         return Err(ContractError::InvalidId {});
     }
 
-    let hash = parse_hex_32(&msg.hash)?;
+    let (algorithm, hash) = parse_encoded_hash(&msg.hash, msg.encoding)?;
+    if let AnchorKind::MerkleRoot { leaves } = msg.kind {
+        if leaves == 0 {
+            return Err(ContractError::EmptyMerkleRoot {});
+        }
+        if algorithm != HashAlgorithm::Sha2_256 {
+            return Err(ContractError::MerkleRootAlgorithm {});
+        }
+    }
+    if let Some(bounding_box) = &msg.bounding_box {
+        bounding_box.validate()?;
+    }
+    if let Some(geohash) = &msg.geohash {
+        geohash_bounds(geohash)?;
+    }
+    let cid = msg.cid.as_deref().map(parse_cid).transpose()?;
+    if let Some(cid) = &cid {
+        if CIDS.has(storage, cid) {
+            return Err(ContractError::CidAlreadyAnchored {});
+        }
+    }
 
     let anchor = Anchor {
         account: msg.account.clone(),
         hash: Binary(hash),
-        source: info.sender,
+        algorithm,
+        source: sender.clone(),
         created: msg.created,
-        validations: vec![],
+        revoked: None,
+        revision: 0,
+        kind: msg.kind,
+        cid: msg.cid,
+        bounding_box: msg.bounding_box,
+        geohash: msg.geohash.clone(),
     };
 
     // Try to store it, fail if the id already exists
-    ANCHORS.update(deps.storage, &msg.id, |existing| match existing {
+    anchors().update(storage, &msg.id, |existing| match existing {
         None => Ok(anchor),
         Some(_) => Err(ContractError::AlreadyExists {}),
     })?;
+    if let Some(cid) = &cid {
+        CIDS.save(storage, cid, &msg.id)?;
+    }
+    if let Some(geohash) = &msg.geohash {
+        GEOHASHES.save(storage, &geohash_key(geohash, &msg.id), &Empty {})?;
+    }
 
-    let res = Response::new()
-        .add_attribute("action", "create")
-        .add_attribute("id", msg.id)
-        .add_attribute("hash", msg.hash)
-        .add_attribute("account", msg.account);
-    Ok(res)
+    Ok(vec![
+        attr("id", msg.id),
+        attr("hash", msg.hash),
+        attr("account", msg.account),
+    ])
+}
+
+/// Loads the config and the validator weight of the sender,
+/// error if it can't validate
+fn load_validating_config(deps: &DepsMut, sender: &Addr) -> Result<(Config, u64), ContractError> {
+    // validators may validate without being admins or users
+    let config = CONFIG.load(deps.storage)?;
+    let weight = validator_weight(deps.storage, &deps.querier, &config, sender);
+    // admins and users validate without weight
+    if weight == 0 && !config.can_execute(sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok((config, weight))
 }
 
 pub fn execute_validate(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: ValidateMsg,
 ) -> Result<Response, ContractError> {
+    let (config, weight) = load_validating_config(&deps, &info.sender)?;
+    let attributes = validate_anchor(deps, &env, &info.sender, &config, weight, msg)?;
+
+    let res = Response::new()
+        .add_attribute("action", "validate")
+        .add_attributes(attributes);
+    Ok(res)
+}
+
+pub fn execute_batch_validate(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msgs: Vec<ValidateMsg>,
+) -> Result<Response, ContractError> {
+    let (config, weight) = load_validating_config(&deps, &info.sender)?;
+    check_batch_size(&config, msgs.len())?;
+
+    // any failure reverts the whole batch
+    let mut res = Response::new()
+        .add_attribute("action", "batch_validate")
+        .add_attribute("count", msgs.len().to_string());
+    for msg in msgs {
+        let attributes = validate_anchor(deps.branch(), &env, &info.sender, &config, weight, msg)?;
+        res = res.add_event(Event::new("validate").add_attributes(attributes));
+    }
+    Ok(res)
+}
+
+/// Stores a validation of the anchor by the sender,
+/// returns the attributes describing it
+fn validate_anchor(
+    deps: DepsMut,
+    env: &Env,
+    sender: &Addr,
+    config: &Config,
+    weight: u64,
+    msg: ValidateMsg,
+) -> Result<Vec<Attribute>, ContractError> {
     if !is_valid_id(&msg.id) {
         return Err(ContractError::InvalidId {});
     }
 
     // Try to load, fail if the id doesn't exist
     // check hash match, fail if not
-    match ANCHORS.load(deps.storage, &msg.id) {
-        Ok(mut anchor) => {
+    match anchors().load(deps.storage, &msg.id) {
+        Ok(anchor) => {
             // if anchor.account != msg.account {
             //     return Err(ContractError::NotAuthorized {});
             // }
-            let hash = parse_hex_32(&msg.hash)?;
-            if anchor.hash != hash {
+            if anchor.revoked.is_some() {
+                return Err(ContractError::Revoked {});
+            }
+            // same algorithm and digest
+            let (algorithm, hash) = parse_encoded_hash(&msg.hash, msg.encoding)?;
+            if anchor.algorithm != algorithm || anchor.hash != hash {
                 return Err(ContractError::HashesDonotMatch {});
             }
 
+            // one validation per source, unless the revalidation interval has passed
+            let key = (msg.id.as_str(), sender);
+            // validations of superseded revisions don't count
+            let last = VALIDATED
+                .may_load(deps.storage, key)?
+                .filter(|(revision, _)| *revision == anchor.revision)
+                .map(|(_, time)| time);
+            if let Some(last) = last {
+                match config.revalidation_interval {
+                    Some(interval) if env.block.time >= last.plus_seconds(interval) => {}
+                    _ => return Err(ContractError::AlreadyValidated {}),
+                }
+            }
+
+            // sources with a registered key must sign
+            let signature = match (PUBLIC_KEYS.may_load(deps.storage, sender)?, msg.signature) {
+                (Some(public_key), Some(signature)) => {
+                    let message = validation_message(
+                        env.contract.address.as_str(),
+                        sender.as_str(),
+                        &msg.id,
+                        &hash,
+                        msg.created,
+                    );
+                    verify_signature(deps.api, &public_key, &message, &signature)?;
+                    Some(ValidationSignature {
+                        public_key,
+                        signature,
+                    })
+                }
+                (Some(_), None) => return Err(ContractError::SignatureRequired {}),
+                (None, Some(_)) => return Err(ContractError::NoPublicKey {}),
+                (None, None) => None,
+            };
+            VALIDATED.save(deps.storage, key, &(anchor.revision, env.block.time))?;
+
+            // validators count once towards the quorum
+            let counted = if last.is_none() { weight } else { 0 };
             let validation = Validation {
                 account: msg.account,
                 hash: Binary(hash),
-                source: info.sender,
+                algorithm,
+                source: sender.clone(),
                 created: msg.created,
+                signature,
+                revision: anchor.revision,
+                weight: counted,
             };
-            anchor.validations.push(validation);
+            let index = add_validation(deps.storage, &msg.id, &validation)?;
 
-            ANCHORS.update(deps.storage, &msg.id, |existing| match existing {
-                Some(_) => Ok(anchor),
-                None => Err(ContractError::AlreadyExists {}),
-            })?;
+            let verified = counted > 0
+                && tally_validation(
+                    deps.storage,
+                    &msg.id,
+                    anchor.revision,
+                    counted,
+                    config.quorum,
+                    env.block.time,
+                )?;
 
-            let res = Response::new()
-                .add_attribute("action", "validate")
-                .add_attribute("id", msg.id)
-                .add_attribute("validated", "true");
-            return Ok(res);
+            let mut attributes = vec![
+                attr("id", msg.id),
+                attr("index", index.to_string()),
+                attr("validated", "true"),
+            ];
+            if verified {
+                attributes.push(attr("verified", "true"));
+            }
+            Ok(attributes)
         }
         Err(_e) => Err(ContractError::NotFound {}),
     }
 }
 
-fn parse_hex_32(data: &str) -> Result<Vec<u8>, ContractError> {
-    match hex::decode(data) {
-        Ok(bin) => {
-            if bin.len() == 32 {
-                Ok(bin)
-            } else {
-                Err(ContractError::InvalidHash(bin.len() * 2))
-            }
-        }
-        Err(e) => Err(ContractError::ParseError(e.to_string())),
+pub fn execute_reject(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: String,
+    hash: String,
+    encoding: HashEncoding,
+    reason: RejectionReason,
+) -> Result<Response, ContractError> {
+    // same sources as validations
+    load_validating_config(&deps, &info.sender)?;
+    if !is_valid_id(&id) {
+        return Err(ContractError::InvalidId {});
     }
-}
-
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::Details { id } => to_binary(&query_details(deps, id)?),
+    let (algorithm, hash) = parse_encoded_hash(&hash, encoding)?;
+    let anchor = anchors()
+        .may_load(deps.storage, &id)?
+        .ok_or(ContractError::NotFound {})?;
+    if anchor.revoked.is_some() {
+        return Err(ContractError::Revoked {});
+    }
+    if REJECTIONS.has(deps.storage, (&id, &info.sender)) {
+        return Err(ContractError::AlreadyRejected {});
     }
-}
-
-fn query_details(deps: Deps, id: String) -> StdResult<DetailsResponse> {
-    let anchor = ANCHORS.load(deps.storage, &id)?;
 
-    let details = DetailsResponse {
-        id,
-        account: anchor.account.into(),
-        hash: hex::encode(anchor.hash.as_slice()),
-        source: anchor.source.into(),
-        created: anchor.created.into(),
-        validations: anchor.validations,
+    let rejection = Rejection {
+        hash: Binary(hash),
+        algorithm,
+        reason,
+        created: env.block.time,
     };
-    Ok(details)
-}
+    add_rejection(deps.storage, &id, &info.sender, &rejection)?;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::state::all_anchor_ids;
-    use chrono::Utc;
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::{coins, from_binary, Timestamp};
-    use sha2::{Digest, Sha256};
+    let res = Response::new()
+        .add_attribute("action", "reject")
+        .add_attribute("id", id)
+        .add_attribute("rejected", "true");
+    Ok(res)
+}
 
-    fn preimage() -> String {
-        hex::encode(b"This is a string, 32 bytes long.")
+pub fn execute_revise(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ReviseMsg,
+) -> Result<Response, ContractError> {
+    if !is_valid_id(&msg.id) {
+        return Err(ContractError::InvalidId {});
     }
-
-    fn custom_preimage(int: u16) -> String {
-        hex::encode(format!("This is a custom string: {:>7}", int))
+    let id = msg.id;
+    let mut anchor = anchors()
+        .may_load(deps.storage, &id)?
+        .ok_or(ContractError::NotFound {})?;
+    let config = CONFIG.load(deps.storage)?;
+    // sources removed from the users lose their anchors
+    let is_source = anchor.source == info.sender && config.can_execute(&info.sender);
+    if !is_source && !config.is_admin(&info.sender) {
+        return Err(ContractError::Unauthorized {});
     }
-
-    fn real_hash() -> String {
-        hex::encode(&Sha256::digest(&hex::decode(preimage()).unwrap()))
+    if anchor.revoked.is_some() {
+        return Err(ContractError::Revoked {});
     }
-
-    fn custom_hash(int: u16) -> String {
-        hex::encode(&Sha256::digest(&hex::decode(custom_preimage(int)).unwrap()))
+    // the challenged hash stays until the challenge is resolved
+    if let Some((_, latest)) = latest_challenge(deps.storage, &id)? {
+        if latest.state == DisputeState::Disputed {
+            return Err(ContractError::ChallengeOpen {});
+        }
+    }
+    let (algorithm, hash) = parse_encoded_hash(&msg.new_hash, msg.encoding)?;
+    if anchor.algorithm == algorithm && anchor.hash == hash {
+        return Err(ContractError::UnchangedHash {});
+    }
+    let kind = match (&anchor.kind, msg.leaves) {
+        (AnchorKind::Object, None) => AnchorKind::Object,
+        (AnchorKind::MerkleRoot { .. }, Some(0)) => return Err(ContractError::EmptyMerkleRoot {}),
+        (AnchorKind::MerkleRoot { .. }, Some(leaves)) => AnchorKind::MerkleRoot { leaves },
+        _ => return Err(ContractError::LeafCount {}),
+    };
+    if matches!(kind, AnchorKind::MerkleRoot { .. }) && algorithm != HashAlgorithm::Sha2_256 {
+        return Err(ContractError::MerkleRootAlgorithm {});
+    }
+    // the corrected geodata has its own CID, or keeps the one of the anchor
+    let cid = msg.cid.as_deref().map(parse_cid).transpose()?;
+    if let Some(cid) = &cid {
+        if matches!(CIDS.may_load(deps.storage, cid)?, Some(other) if other != id) {
+            return Err(ContractError::CidAlreadyAnchored {});
+        }
     }
 
-    fn mock_instantiate_msg() -> InstantiateMsg {
-        let alice = "alice";
-        let bob = "bob";
-        let carl = "carl";
-        let ted = "ted";
-        let instantiate_msg = InstantiateMsg {
-            admins: vec![alice.to_string(), bob.to_string(), carl.to_string()],
-            users: vec![ted.to_string()],
-            mutable: true,
-        };
-        instantiate_msg
+    if let Some(previous) = &anchor.cid {
+        CIDS.remove(deps.storage, &parse_cid(previous)?);
     }
+    if let Some(cid) = &cid {
+        CIDS.save(deps.storage, cid, &id)?;
+    }
+    let previous = Revision {
+        hash: anchor.hash,
+        algorithm: anchor.algorithm,
+        kind: anchor.kind,
+        cid: anchor.cid,
+        created: anchor.created,
+        superseded: env.block.time,
+        by: info.sender,
+    };
+    REVISIONS.save(deps.storage, (&id, anchor.revision), &previous)?;
+    anchor.hash = Binary(hash);
+    anchor.algorithm = algorithm;
+    anchor.kind = kind;
+    anchor.cid = msg.cid;
+    anchor.created = msg.created;
+    anchor.revision += 1;
+    anchors().save(deps.storage, &id, &anchor)?;
 
-    #[test]
-    fn test_instantiate() {
+    let res = Response::new()
+        .add_attribute("action", "revise")
+        .add_attribute("id", id)
+        .add_attribute("hash", msg.new_hash)
+        .add_attribute("revision", anchor.revision.to_string());
+    Ok(res)
+}
+
+pub fn execute_revoke(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: String,
+    reason: String,
+) -> Result<Response, ContractError> {
+    if !is_valid_id(&id) {
+        return Err(ContractError::InvalidId {});
+    }
+    let mut anchor = anchors()
+        .may_load(deps.storage, &id)?
+        .ok_or(ContractError::NotFound {})?;
+    let config = CONFIG.load(deps.storage)?;
+    // sources removed from the users lose their anchors
+    let is_source = anchor.source == info.sender && config.can_execute(&info.sender);
+    if !is_source && !config.is_admin(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    if anchor.revoked.is_some() {
+        return Err(ContractError::Revoked {});
+    }
+
+    anchor.revoked = Some(Revocation {
+        by: info.sender,
+        reason,
+        created: env.block.time,
+    });
+    anchors().save(deps.storage, &id, &anchor)?;
+
+    let res = Response::new()
+        .add_attribute("action", "revoke")
+        .add_attribute("id", id);
+    Ok(res)
+}
+
+pub fn execute_challenge(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: String,
+    validation: Option<u64>,
+    reason: String,
+) -> Result<Response, ContractError> {
+    if !is_valid_id(&id) {
+        return Err(ContractError::InvalidId {});
+    }
+    // the bond is held until the challenge is resolved
+    let config = CONFIG.load(deps.storage)?;
+    let bond = paid_bond(&config, &info.funds)?;
+    // set with every bond
+    let treasury = config.treasury.ok_or(ContractError::TreasuryRequired {})?;
+    if anchors().may_load(deps.storage, &id)?.is_none() {
+        return Err(ContractError::NotFound {});
+    }
+    if let Some(index) = validation {
+        if !VALIDATIONS.has(deps.storage, (&id, index)) {
+            return Err(ContractError::ValidationNotFound {});
+        }
+    }
+    if let Some((_, latest)) = latest_challenge(deps.storage, &id)? {
+        if latest.state == DisputeState::Disputed {
+            return Err(ContractError::ChallengeOpen {});
+        }
+    }
+
+    let challenge = Challenge {
+        anchor_id: id.clone(),
+        validation,
+        challenger: info.sender,
+        bond,
+        treasury,
+        reason,
+        created: env.block.time,
+        state: DisputeState::Disputed,
+        uphold_weight: 0,
+        overturn_weight: 0,
+        resolved: None,
+    };
+    let challenge_id = add_challenge(deps.storage, &challenge)?;
+
+    let res = Response::new()
+        .add_attribute("action", "challenge")
+        .add_attribute("id", id)
+        .add_attribute("challenge_id", challenge_id.to_string());
+    Ok(res)
+}
+
+/// The bond paid with a challenge: exactly the configured bond
+fn paid_bond(config: &Config, funds: &[Coin]) -> Result<Coin, ContractError> {
+    let bond = config
+        .challenge_bond
+        .as_ref()
+        .ok_or(ContractError::NoChallengeBond {})?;
+    match funds.iter().find(|coin| coin.denom == bond.denom) {
+        Some(paid) if paid.amount < bond.amount => {
+            Err(ContractError::InsufficientBond(bond.to_string()))
+        }
+        Some(paid) if paid.amount == bond.amount && funds.len() == 1 => Ok(paid.clone()),
+        Some(_) => Err(ContractError::UnexpectedFunds {}),
+        None => Err(ContractError::InsufficientBond(bond.to_string())),
+    }
+}
+
+fn load_open_challenge(
+    storage: &dyn Storage,
+    challenge_id: u64,
+) -> Result<Challenge, ContractError> {
+    let challenge = CHALLENGES
+        .may_load(storage, challenge_id)?
+        .ok_or(ContractError::ChallengeNotFound {})?;
+    if challenge.state != DisputeState::Disputed {
+        return Err(ContractError::ChallengeResolved {});
+    }
+    Ok(challenge)
+}
+
+pub fn execute_resolve_challenge(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    challenge_id: u64,
+    overturn: bool,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if !config.is_admin(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    let challenge = load_open_challenge(deps.storage, challenge_id)?;
+
+    let res = Response::new().add_attribute("action", "resolve_challenge");
+    let res = close_challenge(
+        deps.storage,
+        res,
+        challenge_id,
+        challenge,
+        overturn,
+        env.block.time,
+    )?;
+    Ok(res)
+}
+
+pub fn execute_vote_challenge(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    challenge_id: u64,
+    overturn: bool,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    // votes could never resolve the challenge
+    if config.quorum.is_none() {
+        return Err(ContractError::NoQuorum {});
+    }
+    let weight = validator_weight(deps.storage, &deps.querier, &config, &info.sender);
+    if weight == 0 {
+        return Err(ContractError::Unauthorized {});
+    }
+    let mut challenge = load_open_challenge(deps.storage, challenge_id)?;
+    if CHALLENGE_VOTES.has(deps.storage, (challenge_id, &info.sender)) {
+        return Err(ContractError::AlreadyVoted {});
+    }
+    CHALLENGE_VOTES.save(deps.storage, (challenge_id, &info.sender), &overturn)?;
+
+    let side = if overturn {
+        &mut challenge.overturn_weight
+    } else {
+        &mut challenge.uphold_weight
+    };
+    *side += weight;
+
+    let res = Response::new().add_attribute("action", "vote_challenge");
+    // resolved once either side reaches the quorum
+    if matches!(config.quorum, Some(q) if *side >= q) {
+        let res = close_challenge(
+            deps.storage,
+            res,
+            challenge_id,
+            challenge,
+            overturn,
+            env.block.time,
+        )?;
+        return Ok(res);
+    }
+    CHALLENGES.save(deps.storage, challenge_id, &challenge)?;
+    Ok(res.add_attribute("challenge_id", challenge_id.to_string()))
+}
+
+/// Resolves the challenge, refunding the bond if the anchor is overturned,
+/// otherwise paying it to the treasury recorded with the challenge.
+/// An overturned validation no longer counts towards the quorum.
+fn close_challenge(
+    storage: &mut dyn Storage,
+    res: Response,
+    challenge_id: u64,
+    mut challenge: Challenge,
+    overturn: bool,
+    time: Timestamp,
+) -> StdResult<Response> {
+    challenge.state = if overturn {
+        DisputeState::Overturned
+    } else {
+        DisputeState::Upheld
+    };
+    challenge.resolved = Some(time);
+    CHALLENGES.save(storage, challenge_id, &challenge)?;
+    if let (true, Some(index)) = (overturn, challenge.validation) {
+        let key = (challenge.anchor_id.as_str(), index);
+        let mut validation = VALIDATIONS.load(storage, key)?;
+        untally_validation(
+            storage,
+            &challenge.anchor_id,
+            validation.revision,
+            validation.weight,
+        )?;
+        validation.weight = 0;
+        VALIDATIONS.save(storage, key, &validation)?;
+    }
+
+    let res = res
+        .add_attribute("challenge_id", challenge_id.to_string())
+        .add_attribute("state", if overturn { "overturned" } else { "upheld" });
+    let to_address = if overturn {
+        challenge.challenger
+    } else {
+        challenge.treasury
+    };
+    let res = res.add_message(BankMsg::Send {
+        to_address: to_address.into(),
+        amount: vec![challenge.bond],
+    });
+    Ok(res)
+}
+
+fn verify_signature(
+    api: &dyn Api,
+    public_key: &PublicKey,
+    message: &[u8],
+    signature: &Binary,
+) -> Result<(), ContractError> {
+    let verified = match public_key.algorithm {
+        KeyAlgorithm::Secp256k1 => {
+            let message_hash = Sha256::digest(message);
+            api.secp256k1_verify(&message_hash, signature, &public_key.key)
+        }
+        KeyAlgorithm::Ed25519 => api.ed25519_verify(message, signature, &public_key.key),
+    }
+    .map_err(StdError::from)?;
+    if !verified {
+        return Err(ContractError::InvalidSignature {});
+    }
+    Ok(())
+}
+
+pub fn execute_register_key(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    algorithm: KeyAlgorithm,
+    key: Binary,
+    signature: Binary,
+) -> Result<Response, ContractError> {
+    // validators register keys to sign their validations
+    load_validating_config(&deps, &info.sender)?;
+    let valid_length = match algorithm {
+        KeyAlgorithm::Secp256k1 => key.len() == 33 || key.len() == 65,
+        KeyAlgorithm::Ed25519 => key.len() == 32,
+    };
+    if !valid_length {
+        return Err(ContractError::InvalidPublicKey {});
+    }
+
+    // only the holder of the key can register it
+    let public_key = PublicKey { algorithm, key };
+    let message = key_registration_message(
+        env.contract.address.as_str(),
+        info.sender.as_str(),
+        &public_key.key,
+    );
+    verify_signature(deps.api, &public_key, &message, &signature)?;
+
+    PUBLIC_KEYS.save(deps.storage, &info.sender, &public_key)?;
+
+    let res = Response::new()
+        .add_attribute("action", "register_key")
+        .add_attribute("source", info.sender);
+    Ok(res)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Details { id, encoding } => to_binary(&query_details(deps, id, encoding)?),
+        QueryMsg::Valid { id, hash, encoding } => {
+            to_binary(&query_valid(deps, id, hash, encoding)?)
+        }
+        QueryMsg::Validations {
+            id,
+            start_after,
+            limit,
+            encoding,
+        } => to_binary(&query_validations(deps, id, start_after, limit, encoding)?),
+        QueryMsg::Rejections {
+            id,
+            start_after,
+            limit,
+            encoding,
+        } => to_binary(&query_rejections(deps, id, start_after, limit, encoding)?),
+        QueryMsg::History { id, encoding } => to_binary(&query_history(deps, id, encoding)?),
+        QueryMsg::Challenge { challenge_id } => to_binary(&query_challenge(deps, challenge_id)?),
+        QueryMsg::Challenges {
+            id,
+            start_after,
+            limit,
+        } => to_binary(&query_challenges(deps, id, start_after, limit)?),
+        QueryMsg::AnchorByCid { cid, encoding } => {
+            to_binary(&query_anchor_by_cid(deps, cid, encoding)?)
+        }
+        QueryMsg::VerifyInclusion {
+            root_id,
+            leaf_hash,
+            proof,
+            encoding,
+        } => to_binary(&query_verify_inclusion(
+            deps, root_id, leaf_hash, proof, encoding,
+        )?),
+        QueryMsg::ListAnchors {
+            start_after,
+            limit,
+            encoding,
+        } => to_binary(&query_list_anchors(deps, start_after, limit, encoding)?),
+        QueryMsg::AnchorsByAccount {
+            account,
+            start_after,
+            limit,
+            encoding,
+        } => to_binary(&query_anchors_by_account(
+            deps,
+            account,
+            start_after,
+            limit,
+            encoding,
+        )?),
+        QueryMsg::AnchorsBySource {
+            source,
+            start_after,
+            limit,
+            encoding,
+        } => to_binary(&query_anchors_by_source(
+            deps,
+            source,
+            start_after,
+            limit,
+            encoding,
+        )?),
+        QueryMsg::AnchorsByHash {
+            hash,
+            start_after,
+            limit,
+            encoding,
+        } => to_binary(&query_anchors_by_hash(
+            deps,
+            hash,
+            start_after,
+            limit,
+            encoding,
+        )?),
+        QueryMsg::AnchorsInGeohash {
+            prefix,
+            start_after,
+            limit,
+            encoding,
+        } => to_binary(&query_anchors_in_geohash(
+            deps,
+            prefix,
+            start_after,
+            limit,
+            encoding,
+        )?),
+        QueryMsg::AnchorsInBoundingBox {
+            bounding_box,
+            start_after,
+            limit,
+            encoding,
+        } => to_binary(&query_anchors_in_bounding_box(
+            deps,
+            bounding_box,
+            start_after,
+            limit,
+            encoding,
+        )?),
+        QueryMsg::AnchorsCreatedBetween {
+            from,
+            to,
+            start_after,
+            limit,
+            encoding,
+        } => to_binary(&query_anchors_created_between(
+            deps,
+            from,
+            to,
+            start_after,
+            limit,
+            encoding,
+        )?),
+        QueryMsg::PublicKey { address } => to_binary(&query_public_key(deps, address)?),
+        QueryMsg::Status { id } => to_binary(&query_status(deps, id)?),
+        QueryMsg::Validators { start_after, limit } => {
+            to_binary(&query_validators(deps, start_after, limit)?)
+        }
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+    }
+}
+
+fn query_valid(
+    deps: Deps,
+    id: String,
+    hash: String,
+    encoding: HashEncoding,
+) -> StdResult<ValidResponse> {
+    let (algorithm, hash) =
+        parse_encoded_hash(&hash, encoding).map_err(|e| StdError::generic_err(e.to_string()))?;
+
+    let res = match anchors().may_load(deps.storage, &id)? {
+        Some(anchor) => ValidResponse {
+            exists: true,
+            hash_matches: anchor.algorithm == algorithm && anchor.hash == hash,
+            validations: validation_count(deps.storage, &id)?,
+            created: Some(anchor.created),
+            revoked: anchor.revoked.is_some(),
+        },
+        None => ValidResponse {
+            exists: false,
+            hash_matches: false,
+            validations: 0,
+            created: None,
+            revoked: false,
+        },
+    };
+    Ok(res)
+}
+
+fn query_verify_inclusion(
+    deps: Deps,
+    root_id: String,
+    leaf_hash: String,
+    proof: Vec<String>,
+    encoding: HashEncoding,
+) -> StdResult<InclusionResponse> {
+    // digests of the leaf and the sha2-256 nodes
+    let parse = |hash: &str| {
+        parse_encoded_hash(hash, encoding)
+            .map(|(_, digest)| digest)
+            .map_err(|e| StdError::generic_err(e.to_string()))
+    };
+    let leaf = parse(&leaf_hash)?;
+    let proof = proof
+        .iter()
+        .map(|hash| parse(hash))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let anchor = anchors().load(deps.storage, &root_id)?;
+    let leaves = match anchor.kind {
+        AnchorKind::MerkleRoot { leaves } => leaves,
+        _ => return Err(StdError::generic_err("Anchor is not a merkle root")),
+    };
+
+    // no proof is deeper than the tree
+    let included = proof.len() <= max_proof_len(leaves)
+        && compute_root(&leaf, &proof) == anchor.hash.as_slice();
+    Ok(InclusionResponse {
+        included,
+        revoked: anchor.revoked.is_some(),
+    })
+}
+
+fn query_list_anchors(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+    encoding: HashEncoding,
+) -> StdResult<ListAnchorsResponse> {
+    let start = start_after.as_deref().map(Bound::exclusive);
+    let iter = anchors().range(deps.storage, start, None, Order::Ascending);
+    list_page(deps.storage, iter, limit, encoding)
+}
+
+fn query_anchors_by_account(
+    deps: Deps,
+    account: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+    encoding: HashEncoding,
+) -> StdResult<ListAnchorsResponse> {
+    let start = start_after.map(Bound::exclusive);
+    let iter =
+        anchors()
+            .idx
+            .account
+            .prefix(account)
+            .range(deps.storage, start, None, Order::Ascending);
+    list_page(deps.storage, iter, limit, encoding)
+}
+
+fn query_anchors_by_source(
+    deps: Deps,
+    source: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+    encoding: HashEncoding,
+) -> StdResult<ListAnchorsResponse> {
+    let source = deps.api.addr_validate(&source)?;
+    let start = start_after.map(Bound::exclusive);
+    let iter =
+        anchors()
+            .idx
+            .source
+            .prefix(source)
+            .range(deps.storage, start, None, Order::Ascending);
+    list_page(deps.storage, iter, limit, encoding)
+}
+
+fn query_anchors_by_hash(
+    deps: Deps,
+    hash: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+    encoding: HashEncoding,
+) -> StdResult<ListAnchorsResponse> {
+    let (algorithm, hash) =
+        parse_encoded_hash(&hash, encoding).map_err(|e| StdError::generic_err(e.to_string()))?;
+    let start = start_after.map(Bound::exclusive);
+    let iter = anchors().idx.hash.prefix((algorithm.code(), hash)).range(
+        deps.storage,
+        start,
+        None,
+        Order::Ascending,
+    );
+    list_page(deps.storage, iter, limit, encoding)
+}
+
+fn query_anchors_created_between(
+    deps: Deps,
+    from: Timestamp,
+    to: Timestamp,
+    start_after: Option<String>,
+    limit: Option<u32>,
+    encoding: HashEncoding,
+) -> StdResult<ListAnchorsResponse> {
+    // index keys are (created, id), so resume after the created time of the last id
+    let start = match start_after {
+        Some(id) => {
+            let anchor = anchors().load(deps.storage, &id)?;
+            if anchor.created < from {
+                Bound::inclusive((from.nanos(), String::new()))
+            } else {
+                Bound::exclusive((anchor.created.nanos(), id))
+            }
+        }
+        None => Bound::inclusive((from.nanos(), String::new())),
+    };
+    let end = Bound::exclusive((to.nanos(), String::new()));
+
+    let iter = anchors()
+        .idx
+        .created
+        .range(deps.storage, Some(start), Some(end), Order::Ascending);
+    list_page(deps.storage, iter, limit, encoding)
+}
+
+/// `geohash_key` of the anchor to resume after, error if it isn't located
+fn geohash_start(deps: Deps, start_after: Option<String>) -> StdResult<Option<Vec<u8>>> {
+    start_after
+        .map(|id| {
+            let anchor = anchors().load(deps.storage, &id)?;
+            let geohash = anchor
+                .geohash
+                .ok_or_else(|| StdError::generic_err("Anchor has no geohash"))?;
+            Ok(geohash_key(&geohash, &id))
+        })
+        .transpose()
+}
+
+fn query_anchors_in_geohash(
+    deps: Deps,
+    prefix: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+    encoding: HashEncoding,
+) -> StdResult<ListAnchorsResponse> {
+    if !prefix.is_empty() {
+        geohash_bounds(&prefix).map_err(|e| StdError::generic_err(e.to_string()))?;
+    }
+    let start = geohash_start(deps, start_after)?;
+    let iter = anchors_in_geohash(deps.storage, &prefix, start).map(|item| {
+        let (_, id) = item?;
+        let anchor = anchors().load(deps.storage, &id)?;
+        Ok((id, anchor))
+    });
+    list_page(deps.storage, iter, limit, encoding)
+}
+
+fn query_anchors_in_bounding_box(
+    deps: Deps,
+    bounding_box: BoundingBox,
+    start_after: Option<String>,
+    limit: Option<u32>,
+    encoding: HashEncoding,
+) -> StdResult<AnchorsInBoundingBoxResponse> {
+    bounding_box
+        .validate()
+        .map_err(|e| StdError::generic_err(e.to_string()))?;
+    let start = geohash_start(deps, start_after)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    // the covering cells, and their ancestors matched exactly by their `geohash_key`
+    // prefix, are disjoint, so sorted their ranges follow in key order.
+    // They spill over the box, so the scan is capped rather than the matches alone.
+    let cells = covering_cells(&bounding_box);
+    let mut prefixes: Vec<String> = ancestor_cells(&cells)
+        .into_iter()
+        .map(|ancestor| format!("{}:", ancestor))
+        .chain(cells)
+        .collect();
+    prefixes.sort();
+    let entries = prefixes
+        .into_iter()
+        .flat_map(|prefix| anchors_in_geohash(deps.storage, &prefix, start.clone()));
+    let mut page = vec![];
+    let mut next = None;
+    for (scanned, item) in entries.enumerate() {
+        let (geohash, id) = item?;
+        let inside = geohash_bounds(&geohash)
+            .map(|cell| {
+                let (lon, lat) = cell.center();
+                bounding_box.contains(lon, lat)
+            })
+            .unwrap_or(false);
+        if inside {
+            let anchor = anchors().load(deps.storage, &id)?;
+            page.push(summarize(deps.storage, id.clone(), anchor, encoding)?);
+        }
+        if page.len() == limit || scanned + 1 == MAX_SCANNED {
+            next = Some(id);
+            break;
+        }
+    }
+    Ok(AnchorsInBoundingBoxResponse {
+        anchors: page,
+        next,
+    })
+}
+
+fn list_page(
+    storage: &dyn Storage,
+    iter: impl Iterator<Item = StdResult<(String, Anchor)>>,
+    limit: Option<u32>,
+    encoding: HashEncoding,
+) -> StdResult<ListAnchorsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let anchors = iter
+        .take(limit)
+        .map(|item| item.and_then(|(id, anchor)| summarize(storage, id, anchor, encoding)))
+        .collect::<StdResult<_>>()?;
+    Ok(ListAnchorsResponse { anchors })
+}
+
+fn summarize(
+    storage: &dyn Storage,
+    id: String,
+    anchor: Anchor,
+    encoding: HashEncoding,
+) -> StdResult<AnchorSummary> {
+    let validations = validation_count(storage, &id)?;
+    Ok(AnchorSummary {
+        id,
+        account: anchor.account,
+        hash: encode_hash_as(anchor.algorithm, &anchor.hash, encoding),
+        source: anchor.source.into(),
+        created: anchor.created,
+        validations,
+    })
+}
+
+fn query_public_key(deps: Deps, address: String) -> StdResult<PublicKeyResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let public_key = PUBLIC_KEYS.may_load(deps.storage, &address)?;
+    Ok(PublicKeyResponse { public_key })
+}
+
+fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    Ok(ConfigResponse {
+        admins: config.admins.into_iter().map(String::from).collect(),
+        users: config.users.into_iter().map(String::from).collect(),
+        mutable: config.mutable,
+        revalidation_interval: config.revalidation_interval,
+        quorum: config.quorum,
+        group: config.group.map(String::from),
+        challenge_bond: config.challenge_bond,
+        treasury: config.treasury.map(String::from),
+        max_batch_size: config.max_batch_size.unwrap_or(DEFAULT_MAX_BATCH_SIZE),
+    })
+}
+
+fn query_details(deps: Deps, id: String, encoding: HashEncoding) -> StdResult<DetailsResponse> {
+    let anchor = anchors().load(deps.storage, &id)?;
+    let validations = validation_count(deps.storage, &id)?;
+    let rejections = rejection_count(deps.storage, &id)?;
+    let dispute =
+        latest_challenge_against(deps.storage, &id, false)?.map(|(_, challenge)| challenge.state);
+    let validation_dispute =
+        latest_challenge_against(deps.storage, &id, true)?.and_then(|(_, challenge)| {
+            challenge.validation.map(|validation| ValidationDispute {
+                validation,
+                state: challenge.state,
+            })
+        });
+    let tally = current_tally(deps.storage, &id, anchor.revision)?;
+    let config = CONFIG.load(deps.storage)?;
+
+    let details = DetailsResponse {
+        id,
+        account: anchor.account,
+        hash: encode_hash_as(anchor.algorithm, &anchor.hash, encoding),
+        source: anchor.source.into(),
+        created: anchor.created,
+        validations,
+        rejections,
+        status: tally.status(config.quorum),
+        dispute,
+        validation_dispute,
+        revoked: anchor.revoked,
+        revision: anchor.revision,
+        kind: anchor.kind,
+        cid: anchor.cid,
+        bounding_box: anchor.bounding_box,
+        geohash: anchor.geohash,
+    };
+    Ok(details)
+}
+
+fn query_anchor_by_cid(
+    deps: Deps,
+    cid: String,
+    encoding: HashEncoding,
+) -> StdResult<DetailsResponse> {
+    let cid = parse_cid(&cid).map_err(|e| StdError::generic_err(e.to_string()))?;
+    let id = CIDS.load(deps.storage, &cid)?;
+    query_details(deps, id, encoding)
+}
+
+fn query_status(deps: Deps, id: String) -> StdResult<StatusResponse> {
+    // error if the anchor doesn't exist, as details
+    let anchor = anchors().load(deps.storage, &id)?;
+    let config = CONFIG.load(deps.storage)?;
+    let tally = current_tally(deps.storage, &id, anchor.revision)?;
+
+    let status = tally.status(config.quorum);
+    let verified = tally.verified.filter(|_| status == AnchorStatus::Verified);
+    Ok(StatusResponse {
+        status,
+        weight: tally.weight,
+        quorum: config.quorum,
+        verified,
+    })
+}
+
+fn query_validators(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<ValidatorsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    let validators = VALIDATORS
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(String::from))
+        .collect::<StdResult<_>>()?;
+    Ok(ValidatorsResponse { validators })
+}
+
+fn query_validations(
+    deps: Deps,
+    id: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    encoding: HashEncoding,
+) -> StdResult<ValidationsResponse> {
+    // error if the anchor doesn't exist, as details
+    anchors().load(deps.storage, &id)?;
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let validations = VALIDATIONS
+        .prefix(&id)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (index, validation) = item?;
+            Ok(ValidationInfo {
+                index,
+                account: validation.account,
+                hash: encode_hash_as(validation.algorithm, &validation.hash, encoding),
+                source: validation.source.into(),
+                created: validation.created,
+                signature: validation.signature,
+                revision: validation.revision,
+                dispute: validation_dispute(deps.storage, &id, index)?,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(ValidationsResponse { validations })
+}
+
+fn query_rejections(
+    deps: Deps,
+    id: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+    encoding: HashEncoding,
+) -> StdResult<RejectionsResponse> {
+    // error if the anchor doesn't exist, as details
+    anchors().load(deps.storage, &id)?;
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    let rejections = REJECTIONS
+        .prefix(&id)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(source, rejection)| RejectionInfo {
+                source: source.into(),
+                hash: encode_hash_as(rejection.algorithm, &rejection.hash, encoding),
+                reason: rejection.reason,
+                created: rejection.created,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(RejectionsResponse { rejections })
+}
+
+fn query_history(deps: Deps, id: String, encoding: HashEncoding) -> StdResult<HistoryResponse> {
+    let anchor = anchors().load(deps.storage, &id)?;
+
+    let mut revisions: Vec<RevisionInfo> = REVISIONS
+        .prefix(&id)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            item.map(|(revision, previous)| RevisionInfo {
+                revision,
+                hash: encode_hash_as(previous.algorithm, &previous.hash, encoding),
+                kind: previous.kind,
+                cid: previous.cid,
+                created: previous.created,
+                superseded: Some(previous.superseded),
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    revisions.push(RevisionInfo {
+        revision: anchor.revision,
+        hash: encode_hash_as(anchor.algorithm, &anchor.hash, encoding),
+        kind: anchor.kind,
+        cid: anchor.cid,
+        created: anchor.created,
+        superseded: None,
+    });
+    Ok(HistoryResponse { revisions })
+}
+
+fn challenge_response(challenge_id: u64, challenge: Challenge) -> ChallengeResponse {
+    ChallengeResponse {
+        challenge_id,
+        anchor_id: challenge.anchor_id,
+        validation: challenge.validation,
+        challenger: challenge.challenger.into(),
+        bond: challenge.bond,
+        treasury: challenge.treasury.into(),
+        reason: challenge.reason,
+        created: challenge.created,
+        state: challenge.state,
+        uphold_weight: challenge.uphold_weight,
+        overturn_weight: challenge.overturn_weight,
+        resolved: challenge.resolved,
+    }
+}
+
+fn query_challenge(deps: Deps, challenge_id: u64) -> StdResult<ChallengeResponse> {
+    let challenge = CHALLENGES.load(deps.storage, challenge_id)?;
+    Ok(challenge_response(challenge_id, challenge))
+}
+
+fn query_challenges(
+    deps: Deps,
+    id: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ChallengesResponse> {
+    // error if the anchor doesn't exist, as details
+    anchors().load(deps.storage, &id)?;
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let challenges = ANCHOR_CHALLENGES
+        .prefix(&id)
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let challenge_id = item?;
+            let challenge = CHALLENGES.load(deps.storage, challenge_id)?;
+            Ok(challenge_response(challenge_id, challenge))
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(ChallengesResponse { challenges })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geo::encode_geohash;
+    use crate::merkle::{hash_leaf, merkle_root};
+    use crate::state::all_anchor_ids;
+    use chrono::Utc;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{
+        coin, coins, from_binary, ContractResult, CosmosMsg, SystemError, SystemResult, Timestamp,
+        WasmQuery,
+    };
+
+    fn preimage() -> String {
+        hex::encode(b"This is a string, 32 bytes long.")
+    }
+
+    fn custom_preimage(int: u16) -> String {
+        hex::encode(format!("This is a custom string: {:>7}", int))
+    }
+
+    fn real_hash() -> String {
+        hex::encode(Sha256::digest(hex::decode(preimage()).unwrap()))
+    }
+
+    fn custom_hash(int: u16) -> String {
+        hex::encode(Sha256::digest(hex::decode(custom_preimage(int)).unwrap()))
+    }
+
+    fn mock_instantiate_msg() -> InstantiateMsg {
+        let alice = "alice";
+        let bob = "bob";
+        let carl = "carl";
+        let ted = "ted";
+        InstantiateMsg {
+            admins: vec![alice.to_string(), bob.to_string(), carl.to_string()],
+            users: vec![
+                ted.to_string(),
+                "sender0001".to_string(),
+                "sender0002".to_string(),
+            ],
+            mutable: true,
+        }
+    }
+
+    fn create_msg(id: String, hash: String) -> CreateMsg {
+        CreateMsg {
+            id,
+            hash,
+            encoding: HashEncoding::Hex,
+            account: String::from("acct0001"),
+            created: Timestamp::from_seconds(1),
+            kind: AnchorKind::Object,
+            cid: None,
+            bounding_box: None,
+            geohash: None,
+        }
+    }
+
+    #[test]
+    fn test_instantiate() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        let res = instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        let config = CONFIG.load(deps.as_ref().storage).unwrap();
+        assert!(config.is_admin("alice"));
+        assert!(config.is_user("ted"));
+        assert!(config.mutable);
+    }
+
+    #[test]
+    fn test_unauthorized() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
+
+        let valid_id = String::from("012345678901234567890123");
+
+        // Cannot create, sender is neither admin nor user
+        let info = mock_info("mallory", &[]);
+        let create = create_msg(valid_id.clone(), real_hash());
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Create(create.clone()),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // Admin can create
+        let admin = mock_info("alice", &[]);
+        execute(deps.as_mut(), mock_env(), admin, ExecuteMsg::Create(create)).unwrap();
+
+        // Cannot validate, sender is neither admin nor user
+        let validate = ValidateMsg {
+            id: valid_id,
+            hash: real_hash(),
+            encoding: HashEncoding::Hex,
+            account: String::from("acct0001"),
+            created: Timestamp::from_seconds(2),
+            signature: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::Validate(validate),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+    }
+
+    #[test]
+    fn test_migrate() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
+
+        let create = create_msg(String::from("012345678901234567890123"), custom_hash(1));
+        let info = mock_info("sender0001", &[]);
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)).unwrap();
+
+        // Same version, nothing to change
+        let msg = MigrateMsg {
+            config: None,
+            limit: None,
+        };
+        let res = migrate(deps.as_mut(), mock_env(), msg.clone()).unwrap();
+        assert_eq!(("migrated", "0"), res.attributes[4]);
+
+        // Older version is upgraded, its anchors indexed again
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.1.0").unwrap();
+        let res = migrate(deps.as_mut(), mock_env(), msg.clone()).unwrap();
+        assert_eq!(("from_version", "0.1.0"), res.attributes[1]);
+        assert_eq!(("migrated", "1"), res.attributes[4]);
+        assert_eq!(("pending", "false"), res.attributes[5]);
+        let version = get_contract_version(deps.as_ref().storage).unwrap();
+        assert_eq!(version.version, CONTRACT_VERSION);
+
+        // Cannot downgrade
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "999.0.0").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), msg.clone()).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::CannotMigrateVersion {
+                previous_version: "999.0.0".to_string()
+            }
+        );
+
+        // Cannot migrate another contract
+        set_contract_version(deps.as_mut().storage, "crates.io:cw20-base", "0.0.1").unwrap();
+        let err = migrate(deps.as_mut(), mock_env(), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::CannotMigrate {
+                previous_contract: "crates.io:cw20-base".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_migrate_without_config() {
+        let mut deps = mock_dependencies();
+
+        // a deployment from before the access lists were stored
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.1.0").unwrap();
+        let msg = MigrateMsg {
+            config: None,
+            limit: None,
+        };
+        let err = migrate(deps.as_mut(), mock_env(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Std(StdError::NotFound { .. })));
+
+        let msg = MigrateMsg {
+            config: Some(mock_instantiate_msg()),
+            limit: None,
+        };
+        migrate(deps.as_mut(), mock_env(), msg).unwrap();
+        let config = CONFIG.load(deps.as_ref().storage).unwrap();
+        assert!(config.is_admin("alice"));
+    }
+
+    #[test]
+    fn test_migrate_pages() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
+        let create = |n: u16| {
+            ExecuteMsg::Create(create_msg(
+                format!("0123456789012345678901{:02}", n),
+                custom_hash(n),
+            ))
+        };
+        let info = mock_info("sender0001", &[]);
+        for n in 0..3 {
+            execute(deps.as_mut(), mock_env(), info.clone(), create(n)).unwrap();
+        }
+
+        // the first page is migrated right away
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.1.0").unwrap();
+        let msg = MigrateMsg {
+            config: None,
+            limit: Some(2),
+        };
+        let res = migrate(deps.as_mut(), mock_env(), msg).unwrap();
+        assert_eq!(("migrated", "2"), res.attributes[4]);
+        assert_eq!(("pending", "true"), res.attributes[5]);
+
+        // nothing else until every anchor is migrated
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), create(3)).unwrap_err();
+        assert_eq!(err, ContractError::MigrationPending {});
+
+        let migrate_anchors = ExecuteMsg::MigrateAnchors { limit: Some(2) };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            migrate_anchors,
+        )
+        .unwrap();
+        assert_eq!(("migrated", "1"), res.attributes[2]);
+        assert_eq!(("pending", "false"), res.attributes[3]);
+        execute(deps.as_mut(), mock_env(), info, create(3)).unwrap();
+    }
+
+    #[test]
+    fn test_update_access_lists() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
+
+        // Users cannot modify the access lists
+        let info = mock_info("ted", &[]);
+        let msg = ExecuteMsg::UpdateAdmins {
+            admins: vec!["ted".to_string()],
+        };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // Admins can rotate both lists
+        let info = mock_info("alice", &[]);
+        let msg = ExecuteMsg::UpdateAdmins {
+            admins: vec!["alice".to_string(), "dave".to_string()],
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::UpdateUsers {
+            users: vec!["erin".to_string()],
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let query_msg = QueryMsg::Config {};
+        let res: ConfigResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(
+            res,
+            ConfigResponse {
+                admins: vec!["alice".to_string(), "dave".to_string()],
+                users: vec!["erin".to_string()],
+                mutable: true,
+                revalidation_interval: None,
+                quorum: None,
+                group: None,
+                challenge_bond: None,
+                treasury: None,
+                max_batch_size: 50,
+            }
+        );
+
+        // Removed admins lose their rights
+        let info = mock_info("bob", &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Freeze {}).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // Once frozen, nobody can modify the access lists
+        let info = mock_info("dave", &[]);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Freeze {},
+        )
+        .unwrap();
+        let msg = ExecuteMsg::UpdateUsers { users: vec![] };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let query_msg = QueryMsg::Config {};
+        let res: ConfigResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert!(!res.mutable);
+    }
+
+    #[test]
+    fn test_create() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
+
+        let sender = String::from("sender0001");
+        let balance = coins(100, "tokens");
+        let valid_id = String::from("012345678901234567890123");
+
+        // Cannot create, invalid ids
+        let info = mock_info(&sender, &balance);
+        for id in &["aa", "aaaabbbbccccd"] {
+            let create = CreateMsg {
+                created: Timestamp::from_nanos(Utc::now().timestamp_nanos() as u64),
+                ..create_msg(id.to_string(), real_hash())
+            };
+            let err = execute(
+                deps.as_mut(),
+                mock_env(),
+                info.clone(),
+                ExecuteMsg::Create(create.clone()),
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::InvalidId {});
+        }
+
+        // Cannot create, invalid hash
+        let info = mock_info(&sender, &balance);
+        let create = CreateMsg {
+            created: Timestamp::from_nanos(Utc::now().timestamp_nanos() as u64),
+            ..create_msg(valid_id.clone(), "bu115h17".to_string())
+        };
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ParseError("Invalid character \'u\' at position 1".into())
+        );
+
+        // Can create, all valid
+        let info = mock_info(&sender, &balance);
+        let create = CreateMsg {
+            created: Timestamp::from_nanos(Utc::now().timestamp_nanos() as u64),
+            ..create_msg(valid_id.clone(), real_hash())
+        };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Create(create),
+        )
+        .unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(("action", "create"), res.attributes[0]);
+
+        // Cannot re-create (modify), already existing
+        let create = CreateMsg {
+            created: Timestamp::from_nanos(Utc::now().timestamp_nanos() as u64),
+            ..create_msg(valid_id.clone(), real_hash())
+        };
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)).unwrap_err();
+        assert_eq!(err, ContractError::AlreadyExists {});
+    }
+
+    #[test]
+    fn test_query() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
+
+        let sender1 = String::from("sender0001");
+        let sender2 = String::from("sender0002");
+        let valid_id1 = String::from("012345678901234567890123");
+        let valid_id2 = String::from("012345678901234567890124");
+
+        // Create 2 anchors
+        let info = mock_info(&sender1, &[]);
+        let create1 = create_msg(valid_id1, custom_hash(1));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::Create(create1.clone()),
+        )
+        .unwrap();
+
+        let info = mock_info(&sender2, &[]);
+        let create2 = CreateMsg {
+            account: String::from("acct0002"),
+            created: Timestamp::from_seconds(2),
+            ..create_msg(valid_id2, custom_hash(2))
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::Create(create2.clone()),
+        )
+        .unwrap();
+
+        let ids = all_anchor_ids(deps.as_mut().storage, None, 10).unwrap();
+        assert_eq!(2, ids.len());
+        // Get the details for the first anchor id
+        let query_msg = QueryMsg::Details {
+            id: ids[0].clone(),
+            encoding: HashEncoding::Hex,
+        };
+        let res: DetailsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(
+            res,
+            DetailsResponse {
+                id: create1.id,
+                hash: create1.hash,
+                account: create1.account,
+                source: sender1,
+                created: create1.created,
+                validations: 0,
+                rejections: 0,
+                status: AnchorStatus::Pending,
+                dispute: None,
+                validation_dispute: None,
+                revoked: None,
+                revision: 0,
+                kind: AnchorKind::Object,
+                cid: None,
+                bounding_box: None,
+                geohash: None,
+            }
+        );
+
+        // Get the details for the second anchor id
+        let query_msg = QueryMsg::Details {
+            id: ids[1].clone(),
+            encoding: HashEncoding::Hex,
+        };
+        let res: DetailsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(
+            res,
+            DetailsResponse {
+                id: create2.id,
+                hash: create2.hash,
+                account: create2.account,
+                source: sender2,
+                created: create2.created,
+                validations: 0,
+                rejections: 0,
+                status: AnchorStatus::Pending,
+                dispute: None,
+                validation_dispute: None,
+                revoked: None,
+                revision: 0,
+                kind: AnchorKind::Object,
+                cid: None,
+                bounding_box: None,
+                geohash: None,
+            }
+        );
+    }
+    #[test]
+    fn test_validate() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
+
+        let sender1 = String::from("sender0001");
+        let valid_id1 = String::from("012345678901234567890123");
+
+        // Create 1 anchors
+        let info = mock_info(&sender1, &[]);
+        let create1 = create_msg(valid_id1.clone(), custom_hash(1));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Create(create1.clone()),
+        )
+        .unwrap();
+
+        let ids = all_anchor_ids(deps.as_mut().storage, None, 10).unwrap();
+        assert_eq!(1, ids.len());
+        // Get the details before validation
+        let query_msg = QueryMsg::Details {
+            id: ids[0].clone(),
+            encoding: HashEncoding::Hex,
+        };
+        let res: DetailsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(
+            res,
+            DetailsResponse {
+                id: create1.id,
+                hash: create1.hash,
+                account: create1.account,
+                source: sender1.clone(),
+                created: create1.created,
+                validations: 0,
+                rejections: 0,
+                status: AnchorStatus::Pending,
+                dispute: None,
+                validation_dispute: None,
+                revoked: None,
+                revision: 0,
+                kind: AnchorKind::Object,
+                cid: None,
+                bounding_box: None,
+                geohash: None,
+            }
+        );
+
+        let validate1 = ValidateMsg {
+            id: valid_id1,
+            hash: custom_hash(1),
+            encoding: HashEncoding::Hex,
+            account: String::from("acct0001"),
+            created: Timestamp::from_seconds(1),
+            signature: None,
+        };
+        let res: Response = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::Validate(validate1.clone()),
+        )
+        .unwrap();
+
+        let mut validated: String = "invalid".to_string();
+        for attribute in res.attributes {
+            if attribute.key == "validated" {
+                validated = attribute.value.to_string();
+                break;
+            }
+        }
+        assert_eq!(validated, "true");
+        // Get the details after validation
+        let query_msg = QueryMsg::Details {
+            id: ids[0].clone(),
+            encoding: HashEncoding::Hex,
+        };
+        let res: DetailsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.validations, 1);
+
+        // Validations are listed separately
+        let query_msg = QueryMsg::Validations {
+            id: ids[0].clone(),
+            start_after: None,
+            limit: None,
+            encoding: HashEncoding::Hex,
+        };
+        let res: ValidationsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(
+            res.validations,
+            vec![ValidationInfo {
+                index: 0,
+                account: validate1.account,
+                hash: validate1.hash,
+                source: sender1,
+                created: validate1.created,
+                signature: None,
+                revision: 0,
+                dispute: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_batch() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
+
+        let admin = mock_info("alice", &[]);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            admin.clone(),
+            ExecuteMsg::SetMaxBatchSize { size: Some(0) },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidBatchSize {});
+        let set_size = ExecuteMsg::SetMaxBatchSize { size: Some(3) };
+        execute(deps.as_mut(), mock_env(), admin, set_size).unwrap();
+
+        let create = |i: u16| create_msg(format!("0123456789012345678{:05}", i), custom_hash(i));
+        let info = mock_info("sender0001", &[]);
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::BatchCreate(vec![]),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::EmptyBatch {});
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::BatchCreate((0..4).map(create).collect()),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::BatchTooLarge { size: 4, max: 3 });
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("stranger", &[]),
+            ExecuteMsg::BatchCreate((0..3).map(create).collect()),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::BatchCreate((0..3).map(create).collect()),
+        )
+        .unwrap();
+        assert_eq!(res.attributes[1].value, "3");
+        assert_eq!(res.events.len(), 3);
+        assert_eq!(res.events[2].ty, "create");
+        assert_eq!(res.events[2].attributes[0].value, create(2).id);
+        let ids = all_anchor_ids(deps.as_ref().storage, None, 10).unwrap();
+        assert_eq!(ids.len(), 3);
+
+        // A failing item fails the batch
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::BatchCreate(vec![create(2)]),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::AlreadyExists {});
+
+        let validate = |i: u16| ValidateMsg {
+            id: format!("0123456789012345678{:05}", i),
+            hash: custom_hash(i),
+            encoding: HashEncoding::Hex,
+            account: String::from("acct0001"),
+            created: Timestamp::from_seconds(2),
+            signature: None,
+        };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::BatchValidate((0..2).map(validate).collect()),
+        )
+        .unwrap();
+        assert_eq!(res.events.len(), 2);
+        assert_eq!(res.events[1].ty, "validate");
+        assert_eq!(res.events[1].attributes[0].value, validate(1).id);
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::BatchValidate(vec![validate(2), validate(1)]),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::AlreadyValidated {});
+    }
+
+    #[test]
+    fn test_multihash() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
+
+        // keccak-256 multihash of the same digest as custom_hash(1)
+        let keccak = format!("1b20{}", custom_hash(1));
+        let valid_id = String::from("012345678901234567890123");
+        let create = |hash: String, kind| CreateMsg {
+            kind,
+            ..create_msg(valid_id.clone(), hash)
+        };
+        let info = mock_info("sender0001", &[]);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Create(create(keccak.clone(), AnchorKind::MerkleRoot { leaves: 2 })),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::MerkleRootAlgorithm {});
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Create(create(
+                format!("1120{}", custom_hash(1)),
+                AnchorKind::Object,
+            )),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::UnsupportedHash(0x11));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Create(create(keccak.clone(), AnchorKind::Object)),
+        )
+        .unwrap();
+
+        // The algorithm must match as well as the digest
+        let validate = |hash: String| {
+            ExecuteMsg::Validate(ValidateMsg {
+                id: valid_id.clone(),
+                hash,
+                encoding: HashEncoding::Hex,
+                account: String::from("acct0001"),
+                created: Timestamp::from_seconds(2),
+                signature: None,
+            })
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            validate(custom_hash(1)),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::HashesDonotMatch {});
+        execute(deps.as_mut(), mock_env(), info, validate(keccak.clone())).unwrap();
+
+        let query_msg = QueryMsg::Details {
+            id: valid_id.clone(),
+            encoding: HashEncoding::Hex,
+        };
+        let res: DetailsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.hash, keccak);
+
+        let query_msg = QueryMsg::AnchorsByHash {
+            hash: custom_hash(1),
+            start_after: None,
+            limit: None,
+            encoding: HashEncoding::Hex,
+        };
+        let res: ListAnchorsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert!(res.anchors.is_empty());
+        let query_msg = QueryMsg::AnchorsByHash {
+            hash: keccak,
+            start_after: None,
+            limit: None,
+            encoding: HashEncoding::Hex,
+        };
+        let res: ListAnchorsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.anchors[0].id, valid_id);
+    }
+
+    #[test]
+    fn test_hash_encodings() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
+
+        let digest = hex::decode(custom_hash(1)).unwrap();
+        let valid_id = String::from("012345678901234567890123");
+        let info = mock_info("sender0001", &[]);
+        let msg = CreateMsg {
+            encoding: HashEncoding::Base64,
+            ..create_msg(valid_id.clone(), Binary(digest.clone()).to_base64())
+        };
+        // base64 is not hex
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Create(CreateMsg {
+                encoding: HashEncoding::Hex,
+                ..msg.clone()
+            }),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::ParseError(_)));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Create(msg),
+        )
+        .unwrap();
+
+        // validated with a base58btc multibase of the same hash
+        let msg = ValidateMsg {
+            id: valid_id.clone(),
+            hash: format!("z{}", bs58::encode(&digest).into_string()),
+            encoding: HashEncoding::Multibase,
+            account: String::from("acct0001"),
+            created: Timestamp::from_seconds(2),
+            signature: None,
+        };
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Validate(msg)).unwrap();
+
+        // rejected with a base64 hash
+        let other = hex::decode(custom_hash(2)).unwrap();
+        let reject = ExecuteMsg::Reject {
+            id: valid_id.clone(),
+            hash: Binary(other.clone()).to_base64(),
+            encoding: HashEncoding::Base64,
+            reason: RejectionReason::HashMismatch,
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender0002", &[]),
+            reject,
+        )
+        .unwrap();
+
+        for encoding in [
+            HashEncoding::Hex,
+            HashEncoding::Base64,
+            HashEncoding::Multibase,
+        ] {
+            let hash = encode_hash_as(HashAlgorithm::Sha2_256, &digest, encoding);
+            let query_msg = QueryMsg::Details {
+                id: valid_id.clone(),
+                encoding,
+            };
+            let res: DetailsResponse =
+                from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+            assert_eq!(res.hash, hash);
+            assert_eq!(res.validations, 1);
+
+            // hashes in and out of queries take the encoding
+            let query_msg = QueryMsg::Valid {
+                id: valid_id.clone(),
+                hash: hash.clone(),
+                encoding,
+            };
+            let res: ValidResponse =
+                from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+            assert!(res.hash_matches);
+            let query_msg = QueryMsg::AnchorsByHash {
+                hash: hash.clone(),
+                start_after: None,
+                limit: None,
+                encoding,
+            };
+            let res: ListAnchorsResponse =
+                from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+            assert_eq!(res.anchors[0].hash, hash);
+            let query_msg = QueryMsg::Validations {
+                id: valid_id.clone(),
+                start_after: None,
+                limit: None,
+                encoding,
+            };
+            let res: ValidationsResponse =
+                from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+            assert_eq!(res.validations[0].hash, hash);
+            let query_msg = QueryMsg::Rejections {
+                id: valid_id.clone(),
+                start_after: None,
+                limit: None,
+                encoding,
+            };
+            let res: RejectionsResponse =
+                from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+            assert_eq!(
+                res.rejections[0].hash,
+                encode_hash_as(HashAlgorithm::Sha2_256, &other, encoding)
+            );
+            let query_msg = QueryMsg::History {
+                id: valid_id.clone(),
+                encoding,
+            };
+            let res: HistoryResponse =
+                from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+            assert_eq!(res.revisions[0].hash, hash);
+        }
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
+
+        // Fiji, across the antimeridian
+        let fiji = BoundingBox {
+            min_lon: 1_770_000_000,
+            min_lat: -210_000_000,
+            max_lon: -1_780_000_000,
+            max_lat: -120_000_000,
+        };
+        let valid_id = String::from("012345678901234567890123");
+        let msg = CreateMsg {
+            bounding_box: Some(BoundingBox {
+                max_lat: 910_000_000,
+                ..fiji
+            }),
+            ..create_msg(valid_id.clone(), custom_hash(1))
+        };
+        let info = mock_info("sender0001", &[]);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Create(msg.clone()),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidBoundingBox("latitude out of range".to_string())
+        );
+        let msg = CreateMsg {
+            bounding_box: Some(fiji),
+            geohash: None,
+            ..msg
+        };
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(msg)).unwrap();
+
+        let query_msg = QueryMsg::Details {
+            id: valid_id,
+            encoding: HashEncoding::Hex,
+        };
+        let res: DetailsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.bounding_box, Some(fiji));
+    }
+
+    #[test]
+    fn test_geohash() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
+
+        let id = |n: u16| format!("0123456789012345678901{:02}", n);
+        let create = |n: u16, geohash: Option<&str>| {
+            ExecuteMsg::Create(CreateMsg {
+                geohash: geohash.map(String::from),
+                ..create_msg(id(n), custom_hash(n))
+            })
+        };
+        let info = mock_info("sender0001", &[]);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            create(0, Some("u4pa")),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidGeohash("u4pa".to_string()));
+        for (n, geohash) in [
+            (0, Some("u4pruydqqvj")),
+            (1, Some("u4pruyd")),
+            (2, Some("u4q")),
+            (3, Some("s00")),
+            (4, None),
+        ] {
+            execute(deps.as_mut(), mock_env(), info.clone(), create(n, geohash)).unwrap();
+        }
+
+        let query_msg = QueryMsg::Details {
+            id: id(0),
+            encoding: HashEncoding::Hex,
+        };
+        let res: DetailsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.geohash, Some("u4pruydqqvj".to_string()));
+
+        let in_geohash = |prefix: &str, start_after: Option<String>, limit| {
+            let query_msg = QueryMsg::AnchorsInGeohash {
+                prefix: prefix.to_string(),
+                start_after,
+                limit,
+                encoding: HashEncoding::Hex,
+            };
+            let res: ListAnchorsResponse =
+                from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+            res.anchors.into_iter().map(|a| a.id).collect::<Vec<_>>()
+        };
+        assert_eq!(in_geohash("u4pr", None, None), vec![id(1), id(0)]);
+        assert_eq!(in_geohash("u4", None, None), vec![id(1), id(0), id(2)]);
+        assert_eq!(in_geohash("", None, None).len(), 4);
+        assert_eq!(in_geohash("u4pr", None, Some(1)), vec![id(1)]);
+        assert_eq!(in_geohash("u4pr", Some(id(1)), None), vec![id(0)]);
+        let query_msg = QueryMsg::AnchorsInGeohash {
+            prefix: "u4a".to_string(),
+            start_after: None,
+            limit: None,
+            encoding: HashEncoding::Hex,
+        };
+        query(deps.as_ref(), mock_env(), query_msg).unwrap_err();
+
+        // located coarsely
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            create(5, Some("u4p")),
+        )
+        .unwrap();
+
+        let in_box = |bounding_box: BoundingBox, start_after: Option<String>| {
+            let query_msg = QueryMsg::AnchorsInBoundingBox {
+                bounding_box,
+                start_after,
+                limit: None,
+                encoding: HashEncoding::Hex,
+            };
+            let res: AnchorsInBoundingBoxResponse =
+                from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+            res.anchors.into_iter().map(|a| a.id).collect::<Vec<_>>()
+        };
+        // around Jutland, the center of "u4q" is out of the box
+        let jutland = BoundingBox {
+            min_lon: 100_000_000,
+            min_lat: 570_000_000,
+            max_lon: 110_000_000,
+            max_lat: 580_000_000,
+        };
+        assert_eq!(in_box(jutland, None), vec![id(1), id(0)]);
+        assert_eq!(in_box(jutland, Some(id(1))), vec![id(0)]);
+        // anchors coarser than the covering cells, centered in the box
+        let kattegat = BoundingBox {
+            min_lon: 104_000_000,
+            min_lat: 568_000_000,
+            max_lon: 107_000_000,
+            max_lat: 571_000_000,
+        };
+        assert!(covering_cells(&kattegat)[0].len() > 3);
+        assert_eq!(in_box(kattegat, None), vec![id(5)]);
+        assert_eq!(in_box(jutland, None), vec![id(1), id(0)]);
+        let null_island = BoundingBox {
+            min_lon: 0,
+            min_lat: 0,
+            max_lon: 20_000_000,
+            max_lat: 20_000_000,
+        };
+        assert_eq!(in_box(null_island, None), vec![id(3)]);
+        // nothing across the antimeridian
+        let pacific = BoundingBox {
+            min_lon: 1_700_000_000,
+            min_lat: -100_000_000,
+            max_lon: -1_700_000_000,
+            max_lat: 100_000_000,
+        };
+        assert!(in_box(pacific, None).is_empty());
+
+        // resuming after an anchor without geohash doesn't restart
+        let query_msg = QueryMsg::AnchorsInBoundingBox {
+            bounding_box: jutland,
+            start_after: Some(id(4)),
+            limit: None,
+            encoding: HashEncoding::Hex,
+        };
+        let err = query(deps.as_ref(), mock_env(), query_msg).unwrap_err();
+        assert_eq!(err, StdError::generic_err("Anchor has no geohash"));
+
+        // anchors in the covering cells but out of the box fill the scan of a page
+        assert!(!jutland.contains(99_000_000, 575_000_000));
+        let outside = encode_geohash(99_000_000, 575_000_000, 12);
+        for n in 0..MAX_SCANNED as u16 {
+            let msg = CreateMsg {
+                geohash: Some(outside.clone()),
+                ..create_msg(format!("01234567890123456780{:04}", n), custom_hash(n))
+            };
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                info.clone(),
+                ExecuteMsg::Create(msg),
+            )
+            .unwrap();
+        }
+        let mut pages = vec![];
+        let mut start_after = None;
+        loop {
+            let query_msg = QueryMsg::AnchorsInBoundingBox {
+                bounding_box: jutland,
+                start_after,
+                limit: None,
+                encoding: HashEncoding::Hex,
+            };
+            let res: AnchorsInBoundingBoxResponse =
+                from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+            pages.push(res.anchors.into_iter().map(|a| a.id).collect::<Vec<_>>());
+            match res.next {
+                Some(next) => start_after = Some(next),
+                None => break,
+            }
+        }
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages.concat(), vec![id(1), id(0)]);
+    }
+
+    #[test]
+    fn test_anchor_by_cid() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
+
+        // the same CID as v0 and as base16 v1
+        let multihash = hex::decode(format!("1220{}", custom_hash(1))).unwrap();
+        let v0 = bs58::encode(&multihash).into_string();
+        let v1 = format!("f0170{}", hex::encode(&multihash));
+
+        let create = |i: u16, cid: &str| CreateMsg {
+            cid: Some(cid.to_string()),
+            ..create_msg(format!("0123456789012345678{:05}", i), custom_hash(i))
+        };
+        let info = mock_info("sender0001", &[]);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Create(create(1, "bafy")),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidCid(_)));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Create(create(1, &v0)),
+        )
+        .unwrap();
+
+        // A CID is anchored once, whatever its encoding
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            ExecuteMsg::Create(create(2, &v1)),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::CidAlreadyAnchored {});
+
+        let query_msg = QueryMsg::AnchorByCid {
+            cid: v1,
+            encoding: HashEncoding::Hex,
+        };
+        let res: DetailsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.id, create(1, &v0).id);
+        assert_eq!(res.cid, Some(v0));
+
+        let query_msg = QueryMsg::AnchorByCid {
+            cid: format!("f0170{}", hex::encode(&multihash).replace('1', "2")),
+            encoding: HashEncoding::Hex,
+        };
+        query(deps.as_ref(), mock_env(), query_msg).unwrap_err();
+    }
+
+    #[test]
+    fn test_verify_inclusion() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
+
+        let leaves: Vec<Vec<u8>> = (1..=3)
+            .map(|i| hex::decode(custom_hash(i)).unwrap())
+            .collect();
+        let root = merkle_root(&leaves).unwrap();
+
+        let root_id = String::from("012345678901234567890123");
+        let create = |kind| CreateMsg {
+            kind,
+            ..create_msg(root_id.clone(), hex::encode(&root))
+        };
+        let info = mock_info("sender0001", &[]);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Create(create(AnchorKind::MerkleRoot { leaves: 0 })),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::EmptyMerkleRoot {});
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Create(create(AnchorKind::MerkleRoot { leaves: 3 })),
+        )
+        .unwrap();
+
+        let verify = |leaf: String, proof: Vec<String>| QueryMsg::VerifyInclusion {
+            root_id: root_id.clone(),
+            leaf_hash: leaf,
+            proof,
+            encoding: HashEncoding::Hex,
+        };
+        let pair = hex::encode(merkle_root(&leaves[..2]).unwrap());
+
+        let query_msg = verify(custom_hash(3), vec![pair.clone()]);
+        let res: InclusionResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert!(res.included);
+        assert!(!res.revoked);
+
+        // sibling leaves are proven by their leaf nodes
+        let node = |i: usize| hex::encode(hash_leaf(&leaves[i]));
+        let query_msg = verify(custom_hash(1), vec![node(1), node(2)]);
+        let res: InclusionResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert!(res.included);
+
+        // Leaves outside the batch aren't included
+        let query_msg = verify(custom_hash(4), vec![node(1), node(2)]);
+        let res: InclusionResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert!(!res.included);
+
+        // nor are inner nodes
+        let query_msg = verify(pair.clone(), vec![node(2)]);
+        let res: InclusionResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert!(!res.included);
+
+        // proofs are at most as deep as the tree of 3 leaves
+        let query_msg = verify(custom_hash(1), vec![node(1), node(2), node(0)]);
+        let res: InclusionResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert!(!res.included);
+
+        let query_msg = verify(custom_hash(1), vec!["bu115h17".to_string()]);
+        query(deps.as_ref(), mock_env(), query_msg).unwrap_err();
+
+        // A revised root takes its new leaf count
+        let revise = |leaves| {
+            ExecuteMsg::Revise(ReviseMsg {
+                id: root_id.clone(),
+                new_hash: pair.clone(),
+                encoding: HashEncoding::Hex,
+                created: Timestamp::from_seconds(2),
+                cid: None,
+                leaves,
+            })
+        };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), revise(None)).unwrap_err();
+        assert_eq!(err, ContractError::LeafCount {});
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), revise(Some(0))).unwrap_err();
+        assert_eq!(err, ContractError::EmptyMerkleRoot {});
+        execute(deps.as_mut(), mock_env(), info.clone(), revise(Some(2))).unwrap();
+        let query_msg = verify(custom_hash(1), vec![node(1)]);
+        let res: InclusionResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert!(res.included);
+        let query_msg = verify(custom_hash(1), vec![node(1), node(2)]);
+        let res: InclusionResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert!(!res.included);
+
+        // Single objects have no proofs
+        let object_id = String::from("012345678901234567890124");
+        let create = create_msg(object_id.clone(), custom_hash(1));
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)).unwrap();
+        let query_msg = QueryMsg::VerifyInclusion {
+            root_id: object_id,
+            leaf_hash: custom_hash(1),
+            proof: vec![],
+            encoding: HashEncoding::Hex,
+        };
+        query(deps.as_ref(), mock_env(), query_msg).unwrap_err();
+    }
+
+    #[test]
+    fn test_reject() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
+
+        let valid_id = String::from("012345678901234567890123");
+        let create = create_msg(valid_id.clone(), custom_hash(1));
+        let info = mock_info("sender0001", &[]);
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)).unwrap();
+
+        let reject = ExecuteMsg::Reject {
+            id: valid_id.clone(),
+            hash: custom_hash(2),
+            reason: RejectionReason::HashMismatch,
+            encoding: HashEncoding::Hex,
+        };
+
+        // Only sources that may validate can reject
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("stranger", &[]),
+            reject.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let missing = ExecuteMsg::Reject {
+            id: String::from("012345678901234567899999"),
+            hash: custom_hash(2),
+            reason: RejectionReason::Unavailable,
+            encoding: HashEncoding::Hex,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("ted", &[]), missing).unwrap_err();
+        assert_eq!(err, ContractError::NotFound {});
+
+        let malformed = ExecuteMsg::Reject {
+            id: valid_id.clone(),
+            hash: String::from("abcd"),
+            reason: RejectionReason::CorruptData,
+            encoding: HashEncoding::Hex,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("ted", &[]), malformed).unwrap_err();
+        assert_eq!(err, ContractError::InvalidHash(4));
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("ted", &[]),
+            reject.clone(),
+        )
+        .unwrap();
+        assert_eq!(res.attributes[2].value, "true");
+
+        // One rejection per source
+        let err = execute(deps.as_mut(), mock_env(), mock_info("ted", &[]), reject).unwrap_err();
+        assert_eq!(err, ContractError::AlreadyRejected {});
+
+        let other = ExecuteMsg::Reject {
+            id: valid_id.clone(),
+            hash: custom_hash(1),
+            reason: RejectionReason::Other(String::from("wrong projection")),
+            encoding: HashEncoding::Hex,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), other).unwrap();
+
+        let query_msg = QueryMsg::Rejections {
+            id: valid_id.clone(),
+            start_after: None,
+            limit: None,
+            encoding: HashEncoding::Hex,
+        };
+        let res: RejectionsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(
+            res.rejections,
+            vec![
+                RejectionInfo {
+                    source: String::from("alice"),
+                    hash: custom_hash(1),
+                    reason: RejectionReason::Other(String::from("wrong projection")),
+                    created: mock_env().block.time,
+                },
+                RejectionInfo {
+                    source: String::from("ted"),
+                    hash: custom_hash(2),
+                    reason: RejectionReason::HashMismatch,
+                    created: mock_env().block.time,
+                },
+            ]
+        );
+
+        let query_msg = QueryMsg::Rejections {
+            id: valid_id.clone(),
+            start_after: Some(String::from("alice")),
+            limit: None,
+            encoding: HashEncoding::Hex,
+        };
+        let res: RejectionsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.rejections.len(), 1);
+        assert_eq!(res.rejections[0].source, "ted");
+
+        // Rejections don't count as validations
+        let query_msg = QueryMsg::Details {
+            id: valid_id,
+            encoding: HashEncoding::Hex,
+        };
+        let res: DetailsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.rejections, 2);
+        assert_eq!(res.validations, 0);
+    }
+
+    #[test]
+    fn test_list_validations() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
+
+        let info = mock_info("sender0001", &[]);
+        let valid_id = String::from("012345678901234567890123");
+        let create = create_msg(valid_id.clone(), custom_hash(1));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Create(create),
+        )
+        .unwrap();
+        let validators = ["alice", "bob", "carl", "ted", "sender0002"];
+        for (i, validator) in validators.iter().enumerate() {
+            let i = i as u64;
+            let validate = ValidateMsg {
+                id: valid_id.clone(),
+                hash: custom_hash(1),
+                encoding: HashEncoding::Hex,
+                account: String::from("acct0001"),
+                created: Timestamp::from_seconds(10 + i),
+                signature: None,
+            };
+            let res = execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(validator, &[]),
+                ExecuteMsg::Validate(validate),
+            )
+            .unwrap();
+            assert_eq!(("index", i.to_string()), res.attributes[2]);
+        }
+
+        let query_msg = QueryMsg::Validations {
+            id: valid_id.clone(),
+            start_after: Some(1),
+            limit: Some(2),
+            encoding: HashEncoding::Hex,
+        };
+        let res: ValidationsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        let created: Vec<u64> = res
+            .validations
+            .iter()
+            .map(|v| v.created.seconds())
+            .collect();
+        assert_eq!(created, vec![12, 13]);
+        assert_eq!(res.validations[0].index, 2);
+
+        // Unknown anchor
+        let query_msg = QueryMsg::Validations {
+            id: String::from("012345678901234567890124"),
+            start_after: None,
+            limit: None,
+            encoding: HashEncoding::Hex,
+        };
+        query(deps.as_ref(), mock_env(), query_msg).unwrap_err();
+    }
+
+    #[test]
+    fn test_duplicate_validation() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
+
+        let info = mock_info("sender0001", &[]);
+        let valid_id = String::from("012345678901234567890123");
+        let create = create_msg(valid_id.clone(), custom_hash(1));
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            ExecuteMsg::Create(create),
+        )
+        .unwrap();
+
+        let validate = ValidateMsg {
+            id: valid_id.clone(),
+            hash: custom_hash(1),
+            encoding: HashEncoding::Hex,
+            account: String::from("acct0001"),
+            created: Timestamp::from_seconds(2),
+            signature: None,
+        };
+        let msg = ExecuteMsg::Validate(validate);
+        execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+
+        // Cannot validate twice
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::AlreadyValidated {});
+
+        // Other sources still can
+        let other = mock_info("sender0002", &[]);
+        execute(deps.as_mut(), mock_env(), other, msg.clone()).unwrap();
+
+        // Only admins set the revalidation interval
+        let set_interval = ExecuteMsg::SetRevalidationInterval {
+            interval: Some(3600),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            set_interval.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let admin = mock_info("alice", &[]);
+        execute(deps.as_mut(), mock_env(), admin, set_interval).unwrap();
+
+        // Too early to validate again
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(3599);
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::AlreadyValidated {});
+
+        // Allowed once the interval has passed, then waits again
+        env.block.time = env.block.time.plus_seconds(1);
+        execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+        let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+        assert_eq!(err, ContractError::AlreadyValidated {});
+
+        let query_msg = QueryMsg::Details {
+            id: valid_id,
+            encoding: HashEncoding::Hex,
+        };
+        let res: DetailsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.validations, 3);
+    }
+
+    #[test]
+    fn test_signed_validation() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
+
+        let valid_id = String::from("012345678901234567890123");
+        let create = create_msg(valid_id.clone(), custom_hash(1));
+        let info = mock_info("sender0001", &[]);
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)).unwrap();
+
+        let created = Timestamp::from_seconds(2);
+        let hash = hex::decode(custom_hash(1)).unwrap();
+        let contract = mock_env().contract.address;
+        let message =
+            |sender: &str| validation_message(contract.as_str(), sender, &valid_id, &hash, created);
+
+        // ed25519 validator
+        let ed_key = ed25519_zebra::SigningKey::from([7u8; 32]);
+        let ed_public = ed25519_zebra::VerificationKey::from(&ed_key);
+        let ed_info = mock_info("alice", &[]);
+        let proof = key_registration_message(contract.as_str(), "alice", ed_public.as_ref());
+        let ed_proof = Binary::from(<[u8; 64]>::from(ed_key.sign(&proof)).to_vec());
+        let register = ExecuteMsg::RegisterKey {
+            algorithm: KeyAlgorithm::Ed25519,
+            key: Binary::from(ed_public.as_ref()),
+            signature: ed_proof.clone(),
+        };
+        execute(deps.as_mut(), mock_env(), ed_info.clone(), register).unwrap();
+
+        // Keys are registered by their holders only
+        let register = ExecuteMsg::RegisterKey {
+            algorithm: KeyAlgorithm::Ed25519,
+            key: Binary::from(ed_public.as_ref()),
+            signature: ed_proof,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("ted", &[]), register).unwrap_err();
+        assert_eq!(err, ContractError::InvalidSignature {});
+
+        // secp256k1 validator
+        let k_key = k256::ecdsa::SigningKey::from_bytes(&[9u8; 32]).unwrap();
+        let k_public = k_key.verifying_key().to_bytes();
+        let k_info = mock_info("bob", &[]);
+        let proof = key_registration_message(contract.as_str(), "bob", k_public.as_slice());
+        let k_proof: k256::ecdsa::Signature = k256::ecdsa::signature::Signer::sign(&k_key, &proof);
+        let register = ExecuteMsg::RegisterKey {
+            algorithm: KeyAlgorithm::Secp256k1,
+            key: Binary::from(k_public.as_slice()),
+            signature: Binary::from(k_proof.as_ref()),
+        };
+        execute(deps.as_mut(), mock_env(), k_info.clone(), register).unwrap();
+
+        let validate = |signature: Option<Binary>| {
+            ExecuteMsg::Validate(ValidateMsg {
+                id: valid_id.clone(),
+                hash: custom_hash(1),
+                encoding: HashEncoding::Hex,
+                account: String::from("acct0001"),
+                created,
+                signature,
+            })
+        };
+
+        // Unsigned, with a registered key
+        let err = execute(deps.as_mut(), mock_env(), ed_info.clone(), validate(None)).unwrap_err();
+        assert_eq!(err, ContractError::SignatureRequired {});
+
+        // Signed with another key
+        let other = ed25519_zebra::SigningKey::from([8u8; 32]);
+        let signature = Binary::from(<[u8; 64]>::from(other.sign(&message("alice"))).to_vec());
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            ed_info.clone(),
+            validate(Some(signature)),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidSignature {});
+
+        // Signed, without a registered key
+        let signature = Binary::from(<[u8; 64]>::from(ed_key.sign(&message("alice"))).to_vec());
+        let no_key = mock_info("carl", &[]);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            no_key,
+            validate(Some(signature.clone())),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NoPublicKey {});
+
+        // Valid signatures
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            ed_info,
+            validate(Some(signature.clone())),
+        )
+        .unwrap();
+        let k_signature: k256::ecdsa::Signature =
+            k256::ecdsa::signature::Signer::sign(&k_key, &message("bob"));
+        let k_signature = Binary::from(k_signature.as_ref());
+        // signatures attest for their sender only
+        let for_alice: k256::ecdsa::Signature =
+            k256::ecdsa::signature::Signer::sign(&k_key, &message("alice"));
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            k_info.clone(),
+            validate(Some(Binary::from(for_alice.as_ref()))),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidSignature {});
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            k_info,
+            validate(Some(k_signature.clone())),
+        )
+        .unwrap();
+
+        // Signatures are kept with the validations, for re-checking off-chain
+        let query_msg = QueryMsg::Validations {
+            id: valid_id,
+            start_after: None,
+            limit: None,
+            encoding: HashEncoding::Hex,
+        };
+        let res: ValidationsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        let signatures: Vec<Binary> = res
+            .validations
+            .into_iter()
+            .map(|v| v.signature.unwrap().signature)
+            .collect();
+        assert_eq!(signatures, vec![signature, k_signature]);
+
+        let query_msg = QueryMsg::PublicKey {
+            address: String::from("bob"),
+        };
+        let res: PublicKeyResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.public_key.unwrap().algorithm, KeyAlgorithm::Secp256k1);
+    }
+
+    #[test]
+    fn test_register_invalid_key() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            mock_instantiate_msg(),
+        )
+        .unwrap();
+
+        let register = ExecuteMsg::RegisterKey {
+            algorithm: KeyAlgorithm::Ed25519,
+            key: Binary::from([1u8; 33].to_vec()),
+            signature: Binary::from([1u8; 64].to_vec()),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &[]),
+            register.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidPublicKey {});
+
+        // Only sources that may validate register keys
+        let err = execute(deps.as_mut(), mock_env(), info, register).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // validators outside the admins and users too
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &[]),
+            ExecuteMsg::UpdateValidators {
+                add: vec!["val1".to_string()],
+                remove: vec![],
+            },
+        )
+        .unwrap();
+        let key = ed25519_zebra::SigningKey::from([1u8; 32]);
+        let public = ed25519_zebra::VerificationKey::from(&key);
+        let contract = mock_env().contract.address;
+        let proof = key_registration_message(contract.as_str(), "val1", public.as_ref());
+        let register = ExecuteMsg::RegisterKey {
+            algorithm: KeyAlgorithm::Ed25519,
+            key: Binary::from(public.as_ref()),
+            signature: Binary::from(<[u8; 64]>::from(key.sign(&proof)).to_vec()),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("val1", &[]), register).unwrap();
+        let query_msg = QueryMsg::PublicKey {
+            address: "val1".to_string(),
+        };
+        let res: PublicKeyResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert!(res.public_key.is_some());
+    }
+
+    #[test]
+    fn test_quorum() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
+
+        // Only admins manage validators and quorum
+        let admin = mock_info("alice", &[]);
+        let update = ExecuteMsg::UpdateValidators {
+            add: vec!["val1".to_string(), "val2".to_string(), "val3".to_string()],
+            remove: vec![],
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("ted", &[]),
+            update.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), admin.clone(), update).unwrap();
+        let update = ExecuteMsg::UpdateValidators {
+            add: vec![],
+            remove: vec!["val3".to_string()],
+        };
+        execute(deps.as_mut(), mock_env(), admin.clone(), update).unwrap();
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            admin.clone(),
+            ExecuteMsg::SetQuorum { quorum: Some(0) },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidQuorum {});
+        let set_quorum = ExecuteMsg::SetQuorum { quorum: Some(2) };
+        execute(deps.as_mut(), mock_env(), admin.clone(), set_quorum).unwrap();
+
+        let query_msg = QueryMsg::Validators {
+            start_after: None,
+            limit: None,
+        };
+        let res: ValidatorsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.validators, vec!["val1".to_string(), "val2".to_string()]);
+
+        let valid_id = String::from("012345678901234567890123");
+        let create = create_msg(valid_id.clone(), custom_hash(1));
+        let info = mock_info("sender0001", &[]);
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)).unwrap();
+
+        let validate = ExecuteMsg::Validate(ValidateMsg {
+            id: valid_id.clone(),
+            hash: custom_hash(1),
+            encoding: HashEncoding::Hex,
+            account: String::from("acct0001"),
+            created: Timestamp::from_seconds(2),
+            signature: None,
+        });
+        let verified = |res: &Response| res.attributes.iter().any(|a| a.key == "verified");
+
+        // Validations by non registered sources don't count
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender0002", &[]),
+            validate.clone(),
+        )
+        .unwrap();
+        assert!(!verified(&res));
+        // Removed validators aren't registered anymore
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("val3", &[]),
+            validate.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("val1", &[]),
+            validate.clone(),
+        )
+        .unwrap();
+        assert!(!verified(&res));
+
+        let query_msg = QueryMsg::Status {
+            id: valid_id.clone(),
+        };
+        let res: StatusResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(
+            res,
+            StatusResponse {
+                status: AnchorStatus::Pending,
+                weight: 1,
+                quorum: Some(2),
+                verified: None,
+            }
+        );
+
+        // Second distinct validator crosses the quorum
+        let res = execute(deps.as_mut(), mock_env(), mock_info("val2", &[]), validate).unwrap();
+        assert!(verified(&res));
+
+        let query_msg = QueryMsg::Details {
+            id: valid_id.clone(),
+            encoding: HashEncoding::Hex,
+        };
+        let res: DetailsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.status, AnchorStatus::Verified);
+        assert_eq!(res.validations, 3);
+
+        // a changed quorum applies to anchors already validated
+        let status = |deps: Deps| -> StatusResponse {
+            let query_msg = QueryMsg::Status {
+                id: valid_id.clone(),
+            };
+            from_binary(&query(deps, mock_env(), query_msg).unwrap()).unwrap()
+        };
+        let set_quorum = |quorum| ExecuteMsg::SetQuorum {
+            quorum: Some(quorum),
+        };
+        execute(deps.as_mut(), mock_env(), admin.clone(), set_quorum(3)).unwrap();
+        let res = status(deps.as_ref());
+        assert_eq!(res.status, AnchorStatus::Pending);
+        assert_eq!(res.verified, None);
+        execute(deps.as_mut(), mock_env(), admin.clone(), set_quorum(2)).unwrap();
+        assert_eq!(status(deps.as_ref()).status, AnchorStatus::Verified);
+        execute(deps.as_mut(), mock_env(), admin, set_quorum(1)).unwrap();
+        assert_eq!(status(deps.as_ref()).status, AnchorStatus::Verified);
+    }
+
+    #[test]
+    fn test_revise() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
+
+        let admin = mock_info("alice", &[]);
+        let update = ExecuteMsg::UpdateValidators {
+            add: vec!["val1".to_string()],
+            remove: vec![],
+        };
+        execute(deps.as_mut(), mock_env(), admin.clone(), update).unwrap();
+        let set_quorum = ExecuteMsg::SetQuorum { quorum: Some(1) };
+        execute(deps.as_mut(), mock_env(), admin, set_quorum).unwrap();
+
+        // CIDv0 of the geodata hashes
+        let cid = |i: u16| {
+            let multihash = hex::decode(format!("1220{}", custom_hash(i))).unwrap();
+            bs58::encode(&multihash).into_string()
+        };
+        let valid_id = String::from("012345678901234567890123");
+        let create = CreateMsg {
+            cid: Some(cid(1)),
+            ..create_msg(valid_id.clone(), custom_hash(1))
+        };
+        let source = mock_info("sender0001", &[]);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            source.clone(),
+            ExecuteMsg::Create(create),
+        )
+        .unwrap();
+        let validate = |hash: String| {
+            ExecuteMsg::Validate(ValidateMsg {
+                id: valid_id.clone(),
+                hash,
+                encoding: HashEncoding::Hex,
+                account: String::from("acct0001"),
+                created: Timestamp::from_seconds(2),
+                signature: None,
+            })
+        };
+        let validator = mock_info("val1", &[]);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            validator.clone(),
+            validate(custom_hash(1)),
+        )
+        .unwrap();
+
+        // Only the source or admins revise, with a new hash
+        let msg = ReviseMsg {
+            id: valid_id.clone(),
+            new_hash: Binary::from(hex::decode(custom_hash(2)).unwrap()).to_base64(),
+            encoding: HashEncoding::Base64,
+            created: Timestamp::from_seconds(3),
+            cid: Some(cid(2)),
+            leaves: None,
+        };
+        let revise = |msg: &ReviseMsg| ExecuteMsg::Revise(msg.clone());
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender0002", &[]),
+            revise(&msg),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let unchanged = ReviseMsg {
+            new_hash: custom_hash(1),
+            encoding: HashEncoding::Hex,
+            ..msg.clone()
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            source.clone(),
+            revise(&unchanged),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::UnchangedHash {});
+        let invalid = ReviseMsg {
+            id: String::from("0123"),
+            ..msg.clone()
+        };
+        let err = execute(deps.as_mut(), mock_env(), source.clone(), revise(&invalid)).unwrap_err();
+        assert_eq!(err, ContractError::InvalidId {});
+        // objects have no leaves
+        let leaves = ReviseMsg {
+            leaves: Some(2),
+            ..msg.clone()
+        };
+        let err = execute(deps.as_mut(), mock_env(), source.clone(), revise(&leaves)).unwrap_err();
+        assert_eq!(err, ContractError::LeafCount {});
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(60);
+        let res = execute(deps.as_mut(), env.clone(), source, revise(&msg)).unwrap();
+        assert_eq!(res.attributes[3].value, "1");
+
+        // The CID follows the revision
+        let query_msg = QueryMsg::AnchorByCid {
+            cid: cid(1),
+            encoding: HashEncoding::Hex,
+        };
+        query(deps.as_ref(), mock_env(), query_msg).unwrap_err();
+        let query_msg = QueryMsg::AnchorByCid {
+            cid: cid(2),
+            encoding: HashEncoding::Hex,
+        };
+        let res: DetailsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.id, valid_id);
+
+        // The new revision is pending until validated again
+        let query_msg = QueryMsg::Details {
+            id: valid_id.clone(),
+            encoding: HashEncoding::Hex,
+        };
+        let res: DetailsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.hash, custom_hash(2));
+        assert_eq!(res.revision, 1);
+        assert_eq!(res.status, AnchorStatus::Pending);
+
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            validator.clone(),
+            validate(custom_hash(1)),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::HashesDonotMatch {});
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            validator,
+            validate(custom_hash(2)),
+        )
+        .unwrap();
+        assert!(res.attributes.iter().any(|a| a.key == "verified"));
+
+        let query_msg = QueryMsg::History {
+            id: valid_id.clone(),
+            encoding: HashEncoding::Hex,
+        };
+        let res: HistoryResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(
+            res.revisions,
+            vec![
+                RevisionInfo {
+                    revision: 0,
+                    hash: custom_hash(1),
+                    kind: AnchorKind::Object,
+                    cid: Some(cid(1)),
+                    created: Timestamp::from_seconds(1),
+                    superseded: Some(env.block.time),
+                },
+                RevisionInfo {
+                    revision: 1,
+                    hash: custom_hash(2),
+                    kind: AnchorKind::Object,
+                    cid: Some(cid(2)),
+                    created: Timestamp::from_seconds(3),
+                    superseded: None,
+                },
+            ]
+        );
+
+        // No revisions under an open challenge
+        let msg = ReviseMsg {
+            new_hash: custom_hash(3),
+            encoding: HashEncoding::Hex,
+            ..msg
+        };
+        let challenge = ExecuteMsg::Challenge {
+            id: valid_id.clone(),
+            validation: None,
+            reason: String::from("wrong hash"),
+        };
+        let set_bond = ExecuteMsg::SetChallengeBond {
+            bond: Some(coin(100, "udcl")),
+            treasury: Some("treasury".to_string()),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), set_bond).unwrap();
+        let info = mock_info("challenger", &coins(100, "udcl"));
+        execute(deps.as_mut(), mock_env(), info, challenge).unwrap();
+        let source = mock_info("sender0001", &[]);
+        let err = execute(deps.as_mut(), mock_env(), source.clone(), revise(&msg)).unwrap_err();
+        assert_eq!(err, ContractError::ChallengeOpen {});
+        let resolve = ExecuteMsg::ResolveChallenge {
+            challenge_id: 0,
+            overturn: false,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), resolve).unwrap();
+
+        // Sources removed from the users can't revise their anchors
+        let update = ExecuteMsg::UpdateUsers {
+            users: vec!["sender0002".to_string()],
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), update).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), source, revise(&msg)).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // A corrected hash keeps the CID of the anchor
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &[]),
+            revise(&msg),
+        )
+        .unwrap();
+        let query_msg = QueryMsg::AnchorByCid {
+            cid: cid(2),
+            encoding: HashEncoding::Hex,
+        };
+        let res: DetailsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.id, valid_id);
+        assert_eq!(res.hash, custom_hash(3));
+    }
+
+    #[test]
+    fn test_revise_same_block() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
+
+        let admin = mock_info("alice", &[]);
+        let update = ExecuteMsg::UpdateValidators {
+            add: vec!["val1".to_string()],
+            remove: vec![],
+        };
+        execute(deps.as_mut(), mock_env(), admin.clone(), update).unwrap();
+        let set_quorum = ExecuteMsg::SetQuorum { quorum: Some(3) };
+        execute(deps.as_mut(), mock_env(), admin, set_quorum).unwrap();
+
+        let valid_id = String::from("012345678901234567890123");
+        let create = create_msg(valid_id.clone(), custom_hash(1));
+        let source = mock_info("sender0001", &[]);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            source.clone(),
+            ExecuteMsg::Create(create),
+        )
+        .unwrap();
+        let validate = |hash: String| {
+            ExecuteMsg::Validate(ValidateMsg {
+                id: valid_id.clone(),
+                hash,
+                encoding: HashEncoding::Hex,
+                account: String::from("acct0001"),
+                created: Timestamp::from_seconds(2),
+                signature: None,
+            })
+        };
+        let validator = mock_info("val1", &[]);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            validator.clone(),
+            validate(custom_hash(1)),
+        )
+        .unwrap();
+        let revise = ExecuteMsg::Revise(ReviseMsg {
+            id: valid_id.clone(),
+            new_hash: custom_hash(2),
+            encoding: HashEncoding::Hex,
+            created: Timestamp::from_seconds(3),
+            cid: None,
+            leaves: None,
+        });
+        execute(deps.as_mut(), mock_env(), source, revise).unwrap();
+
+        // The new revision counts the validator once, within the same block too
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            validator.clone(),
+            validate(custom_hash(2)),
+        )
+        .unwrap();
+        for _ in 0..2 {
+            let err = execute(
+                deps.as_mut(),
+                mock_env(),
+                validator.clone(),
+                validate(custom_hash(2)),
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::AlreadyValidated {});
+        }
+
+        let query_msg = QueryMsg::Status { id: valid_id };
+        let res: StatusResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.weight, 1);
+        assert_eq!(res.status, AnchorStatus::Pending);
+    }
+
+    #[test]
+    fn test_revoke() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
+
+        let valid_id = String::from("012345678901234567890123");
+        let create = create_msg(valid_id.clone(), custom_hash(1));
+        let source = mock_info("sender0001", &[]);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            source.clone(),
+            ExecuteMsg::Create(create.clone()),
+        )
+        .unwrap();
+
+        let revoke = ExecuteMsg::Revoke {
+            id: valid_id.clone(),
+            reason: String::from("corrupt source data"),
+        };
+
+        // Only the source or admins revoke
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender0002", &[]),
+            revoke.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let missing = ExecuteMsg::Revoke {
+            id: String::from("012345678901234567890124"),
+            reason: String::from("corrupt source data"),
+        };
+        let err = execute(deps.as_mut(), mock_env(), source.clone(), missing).unwrap_err();
+        assert_eq!(err, ContractError::NotFound {});
+        let invalid = ExecuteMsg::Revoke {
+            id: String::from("0123"),
+            reason: String::from("corrupt source data"),
+        };
+        let err = execute(deps.as_mut(), mock_env(), source.clone(), invalid).unwrap_err();
+        assert_eq!(err, ContractError::InvalidId {});
+
+        // Sources removed from the users can't revoke their anchors
+        let update = |users: Vec<&str>| ExecuteMsg::UpdateUsers {
+            users: users.into_iter().map(String::from).collect(),
+        };
+        let alice = mock_info("alice", &[]);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            alice.clone(),
+            update(vec!["sender0002"]),
+        )
+        .unwrap();
+        let err = execute(deps.as_mut(), mock_env(), source.clone(), revoke.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            alice,
+            update(vec!["sender0001", "sender0002"]),
+        )
+        .unwrap();
+
+        execute(deps.as_mut(), mock_env(), source.clone(), revoke.clone()).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), revoke).unwrap_err();
+        assert_eq!(err, ContractError::Revoked {});
+
+        // The tombstone keeps the id and can't be validated
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            source.clone(),
+            ExecuteMsg::Create(create),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::AlreadyExists {});
+        let validate = ValidateMsg {
+            id: valid_id.clone(),
+            hash: custom_hash(1),
+            encoding: HashEncoding::Hex,
+            account: String::from("acct0001"),
+            created: Timestamp::from_seconds(2),
+            signature: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            source,
+            ExecuteMsg::Validate(validate),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Revoked {});
+
+        let query_msg = QueryMsg::Valid {
+            id: valid_id.clone(),
+            hash: custom_hash(1),
+            encoding: HashEncoding::Hex,
+        };
+        let res: ValidResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert!(res.exists);
+        assert!(res.revoked);
+
+        let query_msg = QueryMsg::Details {
+            id: valid_id,
+            encoding: HashEncoding::Hex,
+        };
+        let res: DetailsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(
+            res.revoked,
+            Some(Revocation {
+                by: Addr::unchecked("sender0001"),
+                reason: String::from("corrupt source data"),
+                created: mock_env().block.time,
+            })
+        );
+    }
+
+    #[test]
+    fn test_challenge() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
+
+        let admin = mock_info("alice", &[]);
+        let update = ExecuteMsg::UpdateValidators {
+            add: vec!["val1".to_string(), "val2".to_string()],
+            remove: vec![],
+        };
+        execute(deps.as_mut(), mock_env(), admin.clone(), update).unwrap();
+        let set_quorum = ExecuteMsg::SetQuorum { quorum: Some(2) };
+        execute(deps.as_mut(), mock_env(), admin.clone(), set_quorum).unwrap();
+        let set_bond = ExecuteMsg::SetChallengeBond {
+            bond: Some(coin(100, "udcl")),
+            treasury: Some("treasury".to_string()),
+        };
+        execute(deps.as_mut(), mock_env(), admin.clone(), set_bond).unwrap();
+
+        let valid_id = String::from("012345678901234567890123");
+        let create = create_msg(valid_id.clone(), custom_hash(1));
+        let info = mock_info("sender0001", &[]);
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)).unwrap();
+
+        let challenge = ExecuteMsg::Challenge {
+            id: valid_id.clone(),
+            validation: None,
+            reason: String::from("station offline"),
+        };
+
+        // Anyone can challenge, with the bond
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("insurer", &coins(99, "udcl")),
+            challenge.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InsufficientBond("100udcl".to_string()));
+        // exactly the bond, nothing more
+        for funds in [
+            coins(101, "udcl"),
+            vec![coin(100, "udcl"), coin(1, "uatom")],
+        ] {
+            let err = execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info("insurer", &funds),
+                challenge.clone(),
+            )
+            .unwrap_err();
+            assert_eq!(err, ContractError::UnexpectedFunds {});
+        }
+        let missing = ExecuteMsg::Challenge {
+            id: valid_id.clone(),
+            validation: Some(0),
+            reason: String::from("station offline"),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("insurer", &coins(100, "udcl")),
+            missing,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::ValidationNotFound {});
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("insurer", &coins(100, "udcl")),
+            challenge.clone(),
+        )
+        .unwrap();
+        assert_eq!(res.attributes[2].value, "0");
+
+        // One open challenge per anchor
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("insurer", &coins(100, "udcl")),
+            challenge.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::ChallengeOpen {});
+
+        let query_msg = QueryMsg::Details {
+            id: valid_id.clone(),
+            encoding: HashEncoding::Hex,
+        };
+        let res: DetailsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.dispute, Some(DisputeState::Disputed));
+
+        // Only admins resolve, overturning refunds the bond
+        let resolve = ExecuteMsg::ResolveChallenge {
+            challenge_id: 0,
+            overturn: true,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("ted", &[]),
+            resolve.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let res = execute(deps.as_mut(), mock_env(), admin.clone(), resolve.clone()).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "insurer".to_string(),
+                amount: coins(100, "udcl"),
+            })
+        );
+        let err = execute(deps.as_mut(), mock_env(), admin.clone(), resolve).unwrap_err();
+        assert_eq!(err, ContractError::ChallengeResolved {});
+
+        // A new challenge, upheld by the validators
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("insurer", &coins(100, "udcl")),
+            challenge,
+        )
+        .unwrap();
+        let vote = ExecuteMsg::VoteChallenge {
+            challenge_id: 1,
+            overturn: false,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("ted", &[]),
+            vote.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("val1", &[]),
+            vote.clone(),
+        )
+        .unwrap();
+        assert!(!res.attributes.iter().any(|a| a.key == "state"));
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("val1", &[]),
+            vote.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::AlreadyVoted {});
+        let res = execute(deps.as_mut(), mock_env(), mock_info("val2", &[]), vote).unwrap();
+        // the bond is forfeited to the treasury
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "treasury".to_string(),
+                amount: coins(100, "udcl"),
+            })
+        );
+
+        let query_msg = QueryMsg::Challenge { challenge_id: 1 };
+        let res: ChallengeResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.state, DisputeState::Upheld);
+        assert_eq!(res.uphold_weight, 2);
+        assert_eq!(res.bond, coin(100, "udcl"));
+        assert_eq!(res.treasury, "treasury");
+        assert_eq!(res.resolved, Some(mock_env().block.time));
+
+        let query_msg = QueryMsg::Challenges {
+            id: valid_id.clone(),
+            start_after: None,
+            limit: None,
+        };
+        let res: ChallengesResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        let states: Vec<_> = res.challenges.iter().map(|c| c.state).collect();
+        assert_eq!(states, vec![DisputeState::Overturned, DisputeState::Upheld]);
+
+        let query_msg = QueryMsg::Details {
+            id: valid_id.clone(),
+            encoding: HashEncoding::Hex,
+        };
+        let res: DetailsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.dispute, Some(DisputeState::Upheld));
+
+        // A bond is nonzero and comes with a treasury
+        let challenge = ExecuteMsg::Challenge {
+            id: valid_id,
+            validation: None,
+            reason: String::from("station offline"),
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("insurer", &coins(100, "udcl")),
+            challenge.clone(),
+        )
+        .unwrap();
+        let set_bond = ExecuteMsg::SetChallengeBond {
+            bond: Some(coin(0, "udcl")),
+            treasury: Some("treasury".to_string()),
+        };
+        let err = execute(deps.as_mut(), mock_env(), admin.clone(), set_bond).unwrap_err();
+        assert_eq!(err, ContractError::InvalidBond {});
+        let set_bond = ExecuteMsg::SetChallengeBond {
+            bond: Some(coin(100, "udcl")),
+            treasury: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), admin.clone(), set_bond).unwrap_err();
+        assert_eq!(err, ContractError::TreasuryRequired {});
+
+        // Without a bond, challenges are closed
+        let set_bond = ExecuteMsg::SetChallengeBond {
+            bond: None,
+            treasury: None,
+        };
+        execute(deps.as_mut(), mock_env(), admin.clone(), set_bond).unwrap();
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("insurer", &[]),
+            challenge,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NoChallengeBond {});
+
+        // Without a quorum, only admins resolve
+        let set_quorum = ExecuteMsg::SetQuorum { quorum: None };
+        execute(deps.as_mut(), mock_env(), admin.clone(), set_quorum).unwrap();
+        let vote = ExecuteMsg::VoteChallenge {
+            challenge_id: 2,
+            overturn: false,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("val1", &[]), vote).unwrap_err();
+        assert_eq!(err, ContractError::NoQuorum {});
+        let resolve = ExecuteMsg::ResolveChallenge {
+            challenge_id: 2,
+            overturn: false,
+        };
+        // the forfeited bond goes to the treasury of the challenge, unset since
+        let res = execute(deps.as_mut(), mock_env(), admin, resolve).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "treasury".to_string(),
+                amount: coins(100, "udcl"),
+            })
+        );
+    }
+
+    #[test]
+    fn test_challenge_validation() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
+
+        let admin = mock_info("alice", &[]);
+        let update = ExecuteMsg::UpdateValidators {
+            add: vec!["val1".to_string(), "val2".to_string()],
+            remove: vec![],
+        };
+        execute(deps.as_mut(), mock_env(), admin.clone(), update).unwrap();
+        let set_quorum = ExecuteMsg::SetQuorum { quorum: Some(2) };
+        execute(deps.as_mut(), mock_env(), admin.clone(), set_quorum).unwrap();
+        let set_bond = ExecuteMsg::SetChallengeBond {
+            bond: Some(coin(100, "udcl")),
+            treasury: Some("treasury".to_string()),
+        };
+        execute(deps.as_mut(), mock_env(), admin.clone(), set_bond).unwrap();
+
+        let valid_id = String::from("012345678901234567890123");
+        let create = create_msg(valid_id.clone(), custom_hash(1));
+        let info = mock_info("sender0001", &[]);
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)).unwrap();
+        for validator in ["val1", "val2"] {
+            let validate = ExecuteMsg::Validate(ValidateMsg {
+                id: valid_id.clone(),
+                hash: custom_hash(1),
+                encoding: HashEncoding::Hex,
+                account: String::from("acct0001"),
+                created: Timestamp::from_seconds(2),
+                signature: None,
+            });
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(validator, &[]),
+                validate,
+            )
+            .unwrap();
+        }
+
+        let challenge = ExecuteMsg::Challenge {
+            id: valid_id.clone(),
+            validation: Some(1),
+            reason: String::from("never fetched the data"),
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("insurer", &coins(100, "udcl")),
+            challenge,
+        )
+        .unwrap();
+        let resolve = ExecuteMsg::ResolveChallenge {
+            challenge_id: 0,
+            overturn: true,
+        };
+        execute(deps.as_mut(), mock_env(), admin, resolve).unwrap();
+
+        // the dispute is the validation's, which no longer counts
+        let query_msg = QueryMsg::Details {
+            id: valid_id.clone(),
+            encoding: HashEncoding::Hex,
+        };
+        let res: DetailsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.dispute, None);
+        assert_eq!(
+            res.validation_dispute,
+            Some(ValidationDispute {
+                validation: 1,
+                state: DisputeState::Overturned,
+            })
+        );
+        assert_eq!(res.status, AnchorStatus::Pending);
+
+        let query_msg = QueryMsg::Status {
+            id: valid_id.clone(),
+        };
+        let res: StatusResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.weight, 1);
+
+        let query_msg = QueryMsg::Validations {
+            id: valid_id,
+            start_after: None,
+            limit: None,
+            encoding: HashEncoding::Hex,
+        };
+        let res: ValidationsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        let disputes: Vec<_> = res.validations.iter().map(|v| v.dispute).collect();
+        assert_eq!(disputes, vec![None, Some(DisputeState::Overturned)]);
+    }
+
+    #[test]
+    fn test_group_member_user() {
+        let mut deps = mock_dependencies();
+        // ted is a user and a group member with weight 5
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Raw { contract_addr, key } if contract_addr == "group" => {
+                let weight = if key.as_slice() == cw4::member_key("ted").as_slice() {
+                    to_binary(&5u64).unwrap()
+                } else {
+                    Binary::default()
+                };
+                SystemResult::Ok(ContractResult::Ok(weight))
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "wasm".to_string(),
+            }),
+        });
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
+
+        let admin = mock_info("alice", &[]);
+        let set_group = ExecuteMsg::SetGroup {
+            group: Some("group".to_string()),
+        };
+        execute(deps.as_mut(), mock_env(), admin.clone(), set_group).unwrap();
+        let set_quorum = ExecuteMsg::SetQuorum { quorum: Some(5) };
+        execute(deps.as_mut(), mock_env(), admin, set_quorum).unwrap();
+
+        let valid_id = String::from("012345678901234567890123");
+        let create = create_msg(valid_id.clone(), custom_hash(1));
+        let info = mock_info("sender0001", &[]);
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)).unwrap();
+
+        // users outside the group validate without weight
+        let validate = ExecuteMsg::Validate(ValidateMsg {
+            id: valid_id.clone(),
+            hash: custom_hash(1),
+            encoding: HashEncoding::Hex,
+            account: String::from("acct0001"),
+            created: Timestamp::from_seconds(2),
+            signature: None,
+        });
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender0002", &[]),
+            validate.clone(),
+        )
+        .unwrap();
+        let query_msg = QueryMsg::Status {
+            id: valid_id.clone(),
+        };
+        let res: StatusResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.weight, 0);
+        assert_eq!(res.status, AnchorStatus::Pending);
+
+        // a user in the group adds its group weight
+        let res = execute(deps.as_mut(), mock_env(), mock_info("ted", &[]), validate).unwrap();
+        assert!(res.attributes.iter().any(|a| a.key == "verified"));
+
+        let query_msg = QueryMsg::Status { id: valid_id };
+        let res: StatusResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.weight, 5);
+        assert_eq!(res.status, AnchorStatus::Verified);
+    }
+
+    #[test]
+    fn test_group_weights() {
+        let mut deps = mock_dependencies();
+        // cw4 group members: heavy 3, light 1, zero 0
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Raw { contract_addr, key } if contract_addr == "group" => {
+                let weight = [("heavy", 3u64), ("light", 1), ("zero", 0)]
+                    .iter()
+                    .find(|(member, _)| key.as_slice() == cw4::member_key(member).as_slice())
+                    .map(|(_, weight)| to_binary(weight).unwrap())
+                    .unwrap_or_default();
+                SystemResult::Ok(ContractResult::Ok(weight))
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "wasm".to_string(),
+            }),
+        });
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
+
+        let admin = mock_info("alice", &[]);
+        let set_group = ExecuteMsg::SetGroup {
+            group: Some("group".to_string()),
+        };
+        execute(deps.as_mut(), mock_env(), admin.clone(), set_group).unwrap();
+        let set_quorum = ExecuteMsg::SetQuorum { quorum: Some(4) };
+        execute(deps.as_mut(), mock_env(), admin, set_quorum).unwrap();
+
+        let valid_id = String::from("012345678901234567890123");
+        let create = create_msg(valid_id.clone(), custom_hash(1));
+        let info = mock_info("sender0001", &[]);
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)).unwrap();
+
+        let validate = ExecuteMsg::Validate(ValidateMsg {
+            id: valid_id.clone(),
+            hash: custom_hash(1),
+            encoding: HashEncoding::Hex,
+            account: String::from("acct0001"),
+            created: Timestamp::from_seconds(2),
+            signature: None,
+        });
+
+        // Members without weight can't validate
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("zero", &[]),
+            validate.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("heavy", &[]),
+            validate.clone(),
+        )
+        .unwrap();
+        assert!(!res.attributes.iter().any(|a| a.key == "verified"));
+
+        let query_msg = QueryMsg::Status {
+            id: valid_id.clone(),
+        };
+        let res: StatusResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.weight, 3);
+        assert_eq!(res.status, AnchorStatus::Pending);
+
+        let res = execute(deps.as_mut(), mock_env(), mock_info("light", &[]), validate).unwrap();
+        assert!(res.attributes.iter().any(|a| a.key == "verified"));
+
+        let query_msg = QueryMsg::Status {
+            id: valid_id.clone(),
+        };
+        let res: StatusResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.weight, 4);
+        assert_eq!(res.status, AnchorStatus::Verified);
+
+        // a failing group only shuts out its members, users still validate
+        let set_group = ExecuteMsg::SetGroup {
+            group: Some("broken".to_string()),
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("alice", &[]),
+            set_group,
+        )
+        .unwrap();
+        let validate = |n: u64| {
+            ExecuteMsg::Validate(ValidateMsg {
+                id: valid_id.clone(),
+                hash: custom_hash(1),
+                encoding: HashEncoding::Hex,
+                account: String::from("acct0001"),
+                created: Timestamp::from_seconds(n),
+                signature: None,
+            })
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other", &[]),
+            validate(3),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("sender0001", &[]),
+            validate(4),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_list_anchors() {
         let mut deps = mock_dependencies();
 
         let info = mock_info("anyone", &[]);
-        let res = instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
-        assert_eq!(0, res.messages.len());
+        instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
+
+        // Create more anchors than fit in a page
+        let info = mock_info("sender0001", &[]);
+        let ids: Vec<String> = (0..35u16)
+            .map(|i| format!("0123456789012345678{:05}", i))
+            .collect();
+        for (i, id) in ids.iter().enumerate() {
+            let create = CreateMsg {
+                created: Timestamp::from_seconds(i as u64),
+                ..create_msg(id.clone(), custom_hash(i as u16))
+            };
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                info.clone(),
+                ExecuteMsg::Create(create),
+            )
+            .unwrap();
+        }
+
+        // Default page size
+        let query_msg = QueryMsg::ListAnchors {
+            start_after: None,
+            limit: None,
+            encoding: HashEncoding::Hex,
+        };
+        let res: ListAnchorsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.anchors.len(), DEFAULT_LIMIT as usize);
+        assert_eq!(
+            res.anchors[0],
+            AnchorSummary {
+                id: ids[0].clone(),
+                account: String::from("acct0001"),
+                hash: custom_hash(0),
+                source: String::from("sender0001"),
+                created: Timestamp::from_seconds(0),
+                validations: 0,
+            }
+        );
+
+        // Limit is capped
+        let query_msg = QueryMsg::ListAnchors {
+            start_after: None,
+            limit: Some(100),
+            encoding: HashEncoding::Hex,
+        };
+        let res: ListAnchorsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.anchors.len(), MAX_LIMIT as usize);
+
+        // Next page
+        let query_msg = QueryMsg::ListAnchors {
+            start_after: Some(res.anchors[MAX_LIMIT as usize - 1].id.clone()),
+            limit: Some(100),
+            encoding: HashEncoding::Hex,
+        };
+        let res: ListAnchorsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        let page: Vec<String> = res.anchors.into_iter().map(|a| a.id).collect();
+        assert_eq!(page, ids[MAX_LIMIT as usize..].to_vec());
     }
 
     #[test]
-    fn test_create() {
+    fn test_anchors_by_account() {
         let mut deps = mock_dependencies();
 
         let info = mock_info("anyone", &[]);
         instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
 
-        let sender = String::from("sender0001");
-        let balance = coins(100, "tokens");
-        let valid_id = String::from("012345678901234567890123");
-
-        // Cannot create, invalid ids
-        let info = mock_info(&sender, &balance);
-        for id in &["aa", "aaaabbbbccccd"] {
+        // Create anchors alternating between two accounts
+        let info = mock_info("sender0001", &[]);
+        for i in 0..6u16 {
             let create = CreateMsg {
-                id: id.to_string(),
-                hash: real_hash(),
-                account: String::from("acct0001"),
-                created: Timestamp::from_nanos(Utc::now().timestamp_nanos() as u64),
+                account: format!("acct000{}", i % 2),
+                created: Timestamp::from_seconds(i as u64),
+                ..create_msg(format!("0123456789012345678{:05}", i), custom_hash(i))
             };
-            let err = execute(
+            execute(
                 deps.as_mut(),
                 mock_env(),
                 info.clone(),
-                ExecuteMsg::Create(create.clone()),
+                ExecuteMsg::Create(create),
             )
-            .unwrap_err();
-            assert_eq!(err, ContractError::InvalidId {});
+            .unwrap();
         }
 
-        // Cannot create, invalid hash
-        let info = mock_info(&sender, &balance);
-        let create = CreateMsg {
-            id: valid_id.clone(),
-            hash: "bu115h17".to_string(),
+        let query_msg = QueryMsg::AnchorsByAccount {
             account: String::from("acct0001"),
-            created: Timestamp::from_nanos(Utc::now().timestamp_nanos() as u64),
+            start_after: None,
+            limit: Some(2),
+            encoding: HashEncoding::Hex,
         };
-        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)).unwrap_err();
+        let res: ListAnchorsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        let ids: Vec<String> = res.anchors.iter().map(|a| a.id.clone()).collect();
         assert_eq!(
-            err,
-            ContractError::ParseError("Invalid character \'u\' at position 1".into())
+            ids,
+            vec![
+                "012345678901234567800001".to_string(),
+                "012345678901234567800003".to_string()
+            ]
         );
+        assert!(res.anchors.iter().all(|a| a.account == "acct0001"));
 
-        // Can create, all valid
-        let info = mock_info(&sender, &balance);
-        let create = CreateMsg {
-            id: valid_id.clone(),
-            hash: real_hash(),
+        // Next page
+        let query_msg = QueryMsg::AnchorsByAccount {
             account: String::from("acct0001"),
-            created: Timestamp::from_nanos(Utc::now().timestamp_nanos() as u64),
+            start_after: Some(ids[1].clone()),
+            limit: Some(2),
+            encoding: HashEncoding::Hex,
         };
-        let res = execute(
-            deps.as_mut(),
-            mock_env(),
-            info.clone(),
-            ExecuteMsg::Create(create),
-        )
-        .unwrap();
-        assert_eq!(0, res.messages.len());
-        assert_eq!(("action", "create"), res.attributes[0]);
+        let res: ListAnchorsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        let ids: Vec<String> = res.anchors.into_iter().map(|a| a.id).collect();
+        assert_eq!(ids, vec!["012345678901234567800005".to_string()]);
 
-        // Cannot re-create (modify), already existing
-        let create = CreateMsg {
-            id: valid_id.clone(),
-            hash: real_hash(),
-            account: String::from("acct0001"),
-            created: Timestamp::from_nanos(Utc::now().timestamp_nanos() as u64),
+        // Unknown account
+        let query_msg = QueryMsg::AnchorsByAccount {
+            account: String::from("acct0009"),
+            start_after: None,
+            limit: None,
+            encoding: HashEncoding::Hex,
         };
-        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Create(create)).unwrap_err();
-        assert_eq!(err, ContractError::AlreadyExists {});
+        let res: ListAnchorsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert!(res.anchors.is_empty());
     }
 
     #[test]
-    fn test_query() {
+    fn test_anchors_by_source() {
         let mut deps = mock_dependencies();
 
         let info = mock_info("anyone", &[]);
         instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
 
-        let sender1 = String::from("sender0001");
-        let sender2 = String::from("sender0002");
-        let valid_id1 = String::from("012345678901234567890123");
-        let valid_id2 = String::from("012345678901234567890124");
+        // Two workers submit anchors for the same account
+        for i in 0..5u16 {
+            let sender = if i < 3 { "sender0001" } else { "sender0002" };
+            let create = CreateMsg {
+                created: Timestamp::from_seconds(i as u64),
+                ..create_msg(format!("0123456789012345678{:05}", i), custom_hash(i))
+            };
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(sender, &[]),
+                ExecuteMsg::Create(create),
+            )
+            .unwrap();
+        }
 
-        // Create 2 anchors
-        let info = mock_info(&sender1, &[]);
-        let create1 = CreateMsg {
-            id: valid_id1,
-            hash: custom_hash(1),
-            account: String::from("acct0001"),
-            created: Timestamp::from_seconds(1),
+        let query_msg = QueryMsg::AnchorsBySource {
+            source: String::from("sender0002"),
+            start_after: None,
+            limit: None,
+            encoding: HashEncoding::Hex,
         };
-        execute(
-            deps.as_mut(),
-            mock_env(),
-            info,
-            ExecuteMsg::Create(create1.clone()),
-        )
-        .unwrap();
+        let res: ListAnchorsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        let ids: Vec<String> = res.anchors.iter().map(|a| a.id.clone()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                "012345678901234567800003".to_string(),
+                "012345678901234567800004".to_string()
+            ]
+        );
+        assert!(res.anchors.iter().all(|a| a.source == "sender0002"));
 
-        let info = mock_info(&sender2, &[]);
-        let create2 = CreateMsg {
-            id: valid_id2,
-            hash: custom_hash(2),
-            account: String::from("acct0002"),
-            created: Timestamp::from_seconds(2),
+        // Next page
+        let query_msg = QueryMsg::AnchorsBySource {
+            source: String::from("sender0001"),
+            start_after: Some("012345678901234567800000".to_string()),
+            limit: Some(1),
+            encoding: HashEncoding::Hex,
         };
-        execute(
-            deps.as_mut(),
-            mock_env(),
-            info,
-            ExecuteMsg::Create(create2.clone()),
-        )
-        .unwrap();
+        let res: ListAnchorsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        let ids: Vec<String> = res.anchors.into_iter().map(|a| a.id).collect();
+        assert_eq!(ids, vec!["012345678901234567800001".to_string()]);
+    }
 
-        let ids = all_anchor_ids(deps.as_mut().storage, None, 10).unwrap();
-        assert_eq!(2, ids.len());
-        // Get the details for the first anchor id
-        let query_msg = QueryMsg::Details { id: ids[0].clone() };
-        let res: DetailsResponse =
+    #[test]
+    fn test_anchors_by_hash() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
+
+        // The same file is anchored twice, under different ids
+        let info = mock_info("sender0001", &[]);
+        for (i, hash) in [custom_hash(1), custom_hash(2), custom_hash(1)]
+            .iter()
+            .enumerate()
+        {
+            let create = CreateMsg {
+                created: Timestamp::from_seconds(i as u64),
+                ..create_msg(format!("0123456789012345678{:05}", i), hash.clone())
+            };
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                info.clone(),
+                ExecuteMsg::Create(create),
+            )
+            .unwrap();
+        }
+
+        let query_msg = QueryMsg::AnchorsByHash {
+            hash: custom_hash(1),
+            start_after: None,
+            limit: None,
+            encoding: HashEncoding::Hex,
+        };
+        let res: ListAnchorsResponse =
             from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        let ids: Vec<String> = res.anchors.iter().map(|a| a.id.clone()).collect();
         assert_eq!(
-            res,
-            DetailsResponse {
-                id: create1.id,
-                hash: create1.hash,
-                account: create1.account,
-                source: sender1,
-                created: create1.created,
-                validations: vec![],
-            }
+            ids,
+            vec![
+                "012345678901234567800000".to_string(),
+                "012345678901234567800002".to_string()
+            ]
         );
+        assert!(res.anchors.iter().all(|a| a.hash == custom_hash(1)));
 
-        // Get the details for the second anchor id
-        let query_msg = QueryMsg::Details { id: ids[1].clone() };
-        let res: DetailsResponse =
+        // paginated by id
+        let query_msg = QueryMsg::AnchorsByHash {
+            hash: custom_hash(1),
+            start_after: None,
+            limit: Some(1),
+            encoding: HashEncoding::Hex,
+        };
+        let res: ListAnchorsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.anchors.len(), 1);
+        let query_msg = QueryMsg::AnchorsByHash {
+            hash: custom_hash(1),
+            start_after: Some(res.anchors[0].id.clone()),
+            limit: Some(1),
+            encoding: HashEncoding::Hex,
+        };
+        let res: ListAnchorsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.anchors[0].id, "012345678901234567800002");
+
+        // Never anchored
+        let query_msg = QueryMsg::AnchorsByHash {
+            hash: custom_hash(3),
+            start_after: None,
+            limit: None,
+            encoding: HashEncoding::Hex,
+        };
+        let res: ListAnchorsResponse =
             from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert!(res.anchors.is_empty());
+
+        // Malformed hash
+        let query_msg = QueryMsg::AnchorsByHash {
+            hash: "abcd".to_string(),
+            start_after: None,
+            limit: None,
+            encoding: HashEncoding::Hex,
+        };
+        let err = query(deps.as_ref(), mock_env(), query_msg).unwrap_err();
         assert_eq!(
-            res,
-            DetailsResponse {
-                id: create2.id,
-                hash: create2.hash,
-                account: create2.account,
-                source: sender2,
-                created: create2.created,
-                validations: vec![],
-            }
+            err,
+            StdError::generic_err("Invalid hash (4 chars): must be 64 characters or a multihash")
         );
     }
+
     #[test]
-    fn test_validate() {
+    fn test_anchors_created_between() {
         let mut deps = mock_dependencies();
 
         let info = mock_info("anyone", &[]);
         instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
 
-        let sender1 = String::from("sender0001");
-        let valid_id1 = String::from("012345678901234567890123");
+        // Ids in reverse order of created time
+        let info = mock_info("sender0001", &[]);
+        for i in 0..6u16 {
+            let create = CreateMsg {
+                created: Timestamp::from_seconds(100 - 10 * i as u64),
+                ..create_msg(format!("0123456789012345678{:05}", i), custom_hash(i))
+            };
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                info.clone(),
+                ExecuteMsg::Create(create),
+            )
+            .unwrap();
+        }
 
-        // Create 1 anchors
-        let info = mock_info(&sender1, &[]);
-        let create1 = CreateMsg {
-            id: valid_id1.clone(),
-            hash: custom_hash(1),
-            account: String::from("acct0001"),
-            created: Timestamp::from_seconds(1),
+        // [60, 90) holds created 60, 70, 80
+        let query_msg = QueryMsg::AnchorsCreatedBetween {
+            from: Timestamp::from_seconds(60),
+            to: Timestamp::from_seconds(90),
+            start_after: None,
+            limit: Some(2),
+            encoding: HashEncoding::Hex,
+        };
+        let res: ListAnchorsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        let created: Vec<u64> = res.anchors.iter().map(|a| a.created.seconds()).collect();
+        assert_eq!(created, vec![60, 70]);
+
+        // Next page
+        let query_msg = QueryMsg::AnchorsCreatedBetween {
+            from: Timestamp::from_seconds(60),
+            to: Timestamp::from_seconds(90),
+            start_after: Some(res.anchors[1].id.clone()),
+            limit: Some(2),
+            encoding: HashEncoding::Hex,
+        };
+        let res: ListAnchorsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        let ids: Vec<String> = res.anchors.into_iter().map(|a| a.id).collect();
+        assert_eq!(ids, vec!["012345678901234567800002".to_string()]);
+
+        // Empty period
+        let query_msg = QueryMsg::AnchorsCreatedBetween {
+            from: Timestamp::from_seconds(101),
+            to: Timestamp::from_seconds(200),
+            start_after: None,
+            limit: None,
+            encoding: HashEncoding::Hex,
         };
+        let res: ListAnchorsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert!(res.anchors.is_empty());
+    }
+
+    #[test]
+    fn test_query_valid() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info("anyone", &[]);
+        instantiate(deps.as_mut(), mock_env(), info, mock_instantiate_msg()).unwrap();
+
+        let info = mock_info("sender0001", &[]);
+        let valid_id = String::from("012345678901234567890123");
+        let create = create_msg(valid_id.clone(), custom_hash(1));
         execute(
             deps.as_mut(),
             mock_env(),
             info.clone(),
-            ExecuteMsg::Create(create1.clone()),
+            ExecuteMsg::Create(create),
         )
         .unwrap();
-
-        let ids = all_anchor_ids(deps.as_mut().storage, None, 10).unwrap();
-        assert_eq!(1, ids.len());
-        // Get the details before validation
-        let query_msg = QueryMsg::Details { id: ids[0].clone() };
-        let res: DetailsResponse =
-            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
-        assert_eq!(
-            res,
-            DetailsResponse {
-                id: create1.id,
-                hash: create1.hash,
-                account: create1.account,
-                source: sender1,
-                created: create1.created,
-                validations: vec![],
-            }
-        );
-
-        let validate1 = ValidateMsg {
-            id: valid_id1,
+        let validate = ValidateMsg {
+            id: valid_id.clone(),
             hash: custom_hash(1),
+            encoding: HashEncoding::Hex,
             account: String::from("acct0001"),
-            created: Timestamp::from_seconds(1),
+            created: Timestamp::from_seconds(2),
+            signature: None,
         };
-        let res: Response = execute(
+        execute(
             deps.as_mut(),
             mock_env(),
             info,
-            ExecuteMsg::Validate(validate1.clone()),
+            ExecuteMsg::Validate(validate),
         )
         .unwrap();
 
-        let mut validated: String = "invalid".to_string();
-        for attribute in res.attributes {
-            if attribute.key == "validated" {
-                validated = attribute.value.to_string();
-                break;
+        // Matching hash
+        let query_msg = QueryMsg::Valid {
+            id: valid_id.clone(),
+            hash: custom_hash(1),
+            encoding: HashEncoding::Hex,
+        };
+        let res: ValidResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(
+            res,
+            ValidResponse {
+                exists: true,
+                hash_matches: true,
+                validations: 1,
+                created: Some(Timestamp::from_seconds(1)),
+                revoked: false,
             }
-        }
-        assert_eq!(validated, "true");
-        // Get the details after validation
-        let query_msg = QueryMsg::Details { id: ids[0].clone() };
-        let res: DetailsResponse =
+        );
+
+        // Different hash
+        let query_msg = QueryMsg::Valid {
+            id: valid_id.clone(),
+            hash: custom_hash(2),
+            encoding: HashEncoding::Hex,
+        };
+        let res: ValidResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert!(res.exists);
+        assert!(!res.hash_matches);
+
+        // Unknown id
+        let query_msg = QueryMsg::Valid {
+            id: String::from("012345678901234567890124"),
+            hash: custom_hash(1),
+            encoding: HashEncoding::Hex,
+        };
+        let res: ValidResponse =
             from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
-        assert_eq!(res.validations.len(), 1);
+        assert!(!res.exists);
+        assert_eq!(res.created, None);
+
+        // Malformed hash
+        let query_msg = QueryMsg::Valid {
+            id: valid_id,
+            hash: "bu115h17".to_string(),
+            encoding: HashEncoding::Hex,
+        };
+        let err = query(deps.as_ref(), mock_env(), query_msg).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("Hash parse error: Invalid character 'u' at position 1")
+        );
     }
 }
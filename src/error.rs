@@ -6,15 +6,42 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("Semver parsing error: {0}")]
+    SemVer(String),
+
+    #[error("Cannot migrate from different contract type: {previous_contract}")]
+    CannotMigrate { previous_contract: String },
+
+    #[error("Cannot migrate from newer version: {previous_version}")]
+    CannotMigrateVersion { previous_version: String },
+
     #[error("Hash parse error: {0}")]
     ParseError(String),
 
+    #[error("Unauthorized")]
+    Unauthorized {},
+
     #[error("Invalid id")]
     InvalidId {},
 
-    #[error("Invalid hash ({0} chars): must be 64 characters")]
+    #[error("Invalid hash ({0} chars): must be 64 characters or a multihash")]
     InvalidHash(usize),
 
+    #[error("Unsupported multihash code 0x{0:x}")]
+    UnsupportedHash(u64),
+
+    #[error("Invalid CID: {0}")]
+    InvalidCid(String),
+
+    #[error("Invalid bounding box: {0}")]
+    InvalidBoundingBox(String),
+
+    #[error("Invalid geohash: {0}")]
+    InvalidGeohash(String),
+
+    #[error("CID already anchored")]
+    CidAlreadyAnchored,
+
     #[error("Anchor already exists")]
     AlreadyExists,
 
@@ -23,4 +50,91 @@ pub enum ContractError {
 
     #[error("Hashes do not match")]
     HashesDonotMatch,
+
+    #[error("Anchor already validated by this source")]
+    AlreadyValidated,
+
+    #[error("Revision must change the hash")]
+    UnchangedHash,
+
+    #[error("Anchor revoked")]
+    Revoked,
+
+    #[error("Anchor already rejected by this source")]
+    AlreadyRejected,
+
+    #[error("Anchor already has an open challenge")]
+    ChallengeOpen,
+
+    #[error("Validation not found")]
+    ValidationNotFound,
+
+    #[error("Challenge not found")]
+    ChallengeNotFound,
+
+    #[error("Challenge already resolved")]
+    ChallengeResolved,
+
+    #[error("Challenge bond of {0} required")]
+    InsufficientBond(String),
+
+    #[error("Unexpected funds, a challenge takes exactly its bond")]
+    UnexpectedFunds,
+
+    #[error("No challenge bond set, challenges are closed")]
+    NoChallengeBond,
+
+    #[error("Challenge bond must be greater than zero")]
+    InvalidBond,
+
+    #[error("A challenge bond requires a treasury")]
+    TreasuryRequired,
+
+    #[error("No quorum set, admins resolve challenges")]
+    NoQuorum,
+
+    #[error("Challenge already voted on by this validator")]
+    AlreadyVoted,
+
+    #[error("Merkle root must cover at least one leaf")]
+    EmptyMerkleRoot,
+
+    #[error("Merkle root must be a sha2-256 hash")]
+    MerkleRootAlgorithm,
+
+    #[error("A merkle root revision takes its new leaf count, an object revision none")]
+    LeafCount,
+
+    #[error("Batch is empty")]
+    EmptyBatch,
+
+    #[error("Batch of {size} messages exceeds the maximum of {max}")]
+    BatchTooLarge { size: usize, max: u32 },
+
+    #[error("Batch size must be greater than zero")]
+    InvalidBatchSize,
+
+    #[error("Invalid public key")]
+    InvalidPublicKey,
+
+    #[error("No public key registered for this source")]
+    NoPublicKey,
+
+    #[error("Signature required, a public key is registered for this source")]
+    SignatureRequired,
+
+    #[error("Invalid signature")]
+    InvalidSignature,
+
+    #[error("Quorum must be greater than zero")]
+    InvalidQuorum,
+
+    #[error("Migration pending, run migrate_anchors until done")]
+    MigrationPending,
+}
+
+impl From<semver::Error> for ContractError {
+    fn from(err: semver::Error) -> Self {
+        Self::SemVer(err.to_string())
+    }
 }
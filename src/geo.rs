@@ -0,0 +1,326 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ContractError;
+
+/// Fixed-point coordinates are in units of 1e-7 degrees (E7), about 1 cm at the equator
+pub const DEGREE: i32 = 10_000_000;
+pub const MAX_LON: i32 = 180 * DEGREE;
+pub const MAX_LAT: i32 = 90 * DEGREE;
+
+/// Geohash alphabet, base32 without a, i, l and o
+const GEOHASH_ALPHABET: &[u8; 32] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+/// 12 characters locate to a few centimeters
+pub const MAX_GEOHASH_LEN: usize = 12;
+/// Most cells `covering_cells` returns
+pub const MAX_COVERING_CELLS: usize = 32;
+
+/// Longitude/latitude extent of the geodata in E7 degrees.
+/// A box with `min_lon` east of `max_lon` crosses the antimeridian.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, JsonSchema, Debug)]
+pub struct BoundingBox {
+    pub min_lon: i32,
+    pub min_lat: i32,
+    pub max_lon: i32,
+    pub max_lat: i32,
+}
+
+impl BoundingBox {
+    /// Checks coordinate ranges and that the latitudes are in order
+    pub fn validate(&self) -> Result<(), ContractError> {
+        let invalid = |reason: &str| Err(ContractError::InvalidBoundingBox(reason.to_string()));
+        if !(-MAX_LON..=MAX_LON).contains(&self.min_lon)
+            || !(-MAX_LON..=MAX_LON).contains(&self.max_lon)
+        {
+            return invalid("longitude out of range");
+        }
+        if !(-MAX_LAT..=MAX_LAT).contains(&self.min_lat)
+            || !(-MAX_LAT..=MAX_LAT).contains(&self.max_lat)
+        {
+            return invalid("latitude out of range");
+        }
+        if self.min_lat > self.max_lat {
+            return invalid("min_lat above max_lat");
+        }
+        Ok(())
+    }
+
+    pub fn crosses_antimeridian(&self) -> bool {
+        self.min_lon > self.max_lon
+    }
+
+    /// Middle of a box that doesn't cross the antimeridian, as (lon, lat)
+    pub fn center(&self) -> (i32, i32) {
+        (
+            ((i64::from(self.min_lon) + i64::from(self.max_lon)) / 2) as i32,
+            ((i64::from(self.min_lat) + i64::from(self.max_lat)) / 2) as i32,
+        )
+    }
+
+    /// Whether the point is in the box, edges included
+    pub fn contains(&self, lon: i32, lat: i32) -> bool {
+        let in_lon = if self.crosses_antimeridian() {
+            lon >= self.min_lon || lon <= self.max_lon
+        } else {
+            (self.min_lon..=self.max_lon).contains(&lon)
+        };
+        in_lon && (self.min_lat..=self.max_lat).contains(&lat)
+    }
+}
+
+/// Bits of longitude and latitude in a geohash of the length, longitude takes the odd bit
+fn geohash_bits(len: usize) -> (u32, u32) {
+    let bits = 5 * len as u32;
+    (bits.div_ceil(2), bits / 2)
+}
+
+/// Bounds of the cell `index` of `2^bits` cells over [-max, max]
+fn cell_bounds(index: u64, bits: u32, max: i32) -> (i32, i32) {
+    let span = 2 * i64::from(max);
+    let min = -i64::from(max) + ((index as i64 * span) >> bits);
+    let next = -i64::from(max) + (((index as i64 + 1) * span) >> bits);
+    (min as i32, next as i32)
+}
+
+/// Cell of `2^bits` cells over [-max, max] holding the coordinate
+fn cell_index(value: i32, bits: u32, max: i32) -> u64 {
+    let offset = i64::from(value) + i64::from(max);
+    let index = (offset << bits) / (2 * i64::from(max));
+    // the upper edge belongs to the last cell
+    index.min((1 << bits) - 1) as u64
+}
+
+/// Decodes a lowercase geohash of 1 to `MAX_GEOHASH_LEN` characters into its cell
+pub fn geohash_bounds(geohash: &str) -> Result<BoundingBox, ContractError> {
+    if geohash.is_empty() || geohash.len() > MAX_GEOHASH_LEN {
+        return Err(ContractError::InvalidGeohash(geohash.to_string()));
+    }
+    let (mut lon, mut lat) = (0u64, 0u64);
+    let mut even = true;
+    for c in geohash.bytes() {
+        let value = GEOHASH_ALPHABET
+            .iter()
+            .position(|a| *a == c)
+            .ok_or_else(|| ContractError::InvalidGeohash(geohash.to_string()))?;
+        for shift in (0..5).rev() {
+            let bit = (value >> shift) as u64 & 1;
+            if even {
+                lon = (lon << 1) | bit;
+            } else {
+                lat = (lat << 1) | bit;
+            }
+            even = !even;
+        }
+    }
+
+    let (lon_bits, lat_bits) = geohash_bits(geohash.len());
+    let (min_lon, max_lon) = cell_bounds(lon, lon_bits, MAX_LON);
+    let (min_lat, max_lat) = cell_bounds(lat, lat_bits, MAX_LAT);
+    Ok(BoundingBox {
+        min_lon,
+        min_lat,
+        max_lon,
+        max_lat,
+    })
+}
+
+/// Geohash of the given length of the cell at the longitude and latitude indexes
+fn encode_cell(lon: u64, lat: u64, len: usize) -> String {
+    let (mut lon_bits, mut lat_bits) = geohash_bits(len);
+    let mut geohash = String::with_capacity(len);
+    let mut even = true;
+    let mut value = 0;
+    for i in 0..5 * len {
+        let bit = if even {
+            lon_bits -= 1;
+            (lon >> lon_bits) & 1
+        } else {
+            lat_bits -= 1;
+            (lat >> lat_bits) & 1
+        };
+        value = (value << 1) | bit as usize;
+        even = !even;
+        if i % 5 == 4 {
+            geohash.push(GEOHASH_ALPHABET[value] as char);
+            value = 0;
+        }
+    }
+    geohash
+}
+
+/// Geohash of the given length of the cell holding the point
+pub fn encode_geohash(lon: i32, lat: i32, len: usize) -> String {
+    let (lon_bits, lat_bits) = geohash_bits(len);
+    encode_cell(
+        cell_index(lon, lon_bits, MAX_LON),
+        cell_index(lat, lat_bits, MAX_LAT),
+        len,
+    )
+}
+
+/// Sorted geohash cells covering the box, of the greatest length
+/// needing at most `MAX_COVERING_CELLS` cells
+pub fn covering_cells(bounding_box: &BoundingBox) -> Vec<String> {
+    // split at the antimeridian
+    let lon_ranges = if bounding_box.crosses_antimeridian() {
+        vec![
+            (bounding_box.min_lon, MAX_LON),
+            (-MAX_LON, bounding_box.max_lon),
+        ]
+    } else {
+        vec![(bounding_box.min_lon, bounding_box.max_lon)]
+    };
+    let ranges = |len: usize| {
+        let (lon_bits, lat_bits) = geohash_bits(len);
+        let lons: Vec<(u64, u64)> = lon_ranges
+            .iter()
+            .map(|(min, max)| {
+                (
+                    cell_index(*min, lon_bits, MAX_LON),
+                    cell_index(*max, lon_bits, MAX_LON),
+                )
+            })
+            .collect();
+        let lats = (
+            cell_index(bounding_box.min_lat, lat_bits, MAX_LAT),
+            cell_index(bounding_box.max_lat, lat_bits, MAX_LAT),
+        );
+        (lons, lats)
+    };
+    let count = |(lons, lats): &(Vec<(u64, u64)>, (u64, u64))| {
+        let columns: u64 = lons.iter().map(|(min, max)| max - min + 1).sum();
+        columns * (lats.1 - lats.0 + 1)
+    };
+
+    // a single character needs at most 8 by 4 cells
+    let len = (2..=MAX_GEOHASH_LEN)
+        .take_while(|len| count(&ranges(*len)) <= MAX_COVERING_CELLS as u64)
+        .last()
+        .unwrap_or(1);
+    let (lons, lats) = ranges(len);
+    let mut cells: Vec<String> = lons
+        .iter()
+        .flat_map(|(min, max)| *min..=*max)
+        .flat_map(|lon| (lats.0..=lats.1).map(move |lat| encode_cell(lon, lat, len)))
+        .collect();
+    cells.sort();
+    cells.dedup();
+    cells
+}
+
+/// Sorted geohashes shorter than the covering cells and holding them.
+/// Anchors located this coarsely may still be centered in the box.
+pub fn ancestor_cells(cells: &[String]) -> Vec<String> {
+    let mut ancestors: Vec<String> = cells
+        .iter()
+        .flat_map(|cell| (1..cell.len()).map(move |len| cell[..len].to_string()))
+        .collect();
+    ancestors.sort();
+    ancestors.dedup();
+    ancestors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bbox(min_lon: i32, min_lat: i32, max_lon: i32, max_lat: i32) -> BoundingBox {
+        BoundingBox {
+            min_lon: min_lon * DEGREE,
+            min_lat: min_lat * DEGREE,
+            max_lon: max_lon * DEGREE,
+            max_lat: max_lat * DEGREE,
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let invalid = |reason: &str| Err(ContractError::InvalidBoundingBox(reason.to_string()));
+        assert_eq!(bbox(-180, -90, 180, 90).validate(), Ok(()));
+        assert_eq!(bbox(170, -10, -170, 10).validate(), Ok(()));
+        assert_eq!(
+            bbox(-181, 0, 10, 10).validate(),
+            invalid("longitude out of range")
+        );
+        assert_eq!(
+            bbox(0, 0, 10, 91).validate(),
+            invalid("latitude out of range")
+        );
+        assert_eq!(
+            bbox(0, 10, 10, 0).validate(),
+            invalid("min_lat above max_lat")
+        );
+    }
+
+    #[test]
+    fn test_contains() {
+        let boxed = bbox(-10, -10, 10, 10);
+        assert!(!boxed.crosses_antimeridian());
+        assert!(boxed.contains(0, 0));
+        assert!(boxed.contains(10 * DEGREE, -10 * DEGREE));
+        assert!(!boxed.contains(11 * DEGREE, 0));
+
+        // across the antimeridian, from 170E to 170W
+        let pacific = bbox(170, -10, -170, 10);
+        assert!(pacific.crosses_antimeridian());
+        assert!(pacific.contains(MAX_LON, 0));
+        assert!(pacific.contains(-175 * DEGREE, 0));
+        assert!(!pacific.contains(0, 0));
+        assert!(!pacific.contains(175 * DEGREE, 11 * DEGREE));
+    }
+
+    #[test]
+    fn test_geohash() {
+        // the classic example, near Jutland
+        let cell = geohash_bounds("u4pruydqqvj").unwrap();
+        let (lon, lat) = cell.center();
+        assert_eq!(lon / 1000, 104_074);
+        assert_eq!(lat / 1000, 576_491);
+        assert_eq!(encode_geohash(lon, lat, 11), "u4pruydqqvj");
+        assert_eq!(encode_geohash(lon, lat, 3), "u4p");
+
+        assert_eq!(geohash_bounds("s").unwrap(), bbox(0, 0, 45, 45));
+        assert_eq!(encode_geohash(MAX_LON, MAX_LAT, 1), "z");
+        assert_eq!(encode_geohash(-MAX_LON, -MAX_LAT, 1), "0");
+
+        for invalid in ["", "u4pa", "U4P", "u4pruydqqvjxx"] {
+            assert_eq!(
+                geohash_bounds(invalid),
+                Err(ContractError::InvalidGeohash(invalid.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_covering_cells() {
+        // all 32 cells of length 2 in "s"
+        let cells = covering_cells(&bbox(1, 1, 44, 44));
+        assert_eq!(cells.len(), 32);
+        assert!(cells.iter().all(|cell| cell.starts_with('s')));
+        assert_eq!(covering_cells(&bbox(-180, -90, 180, 90)).len(), 32);
+
+        let cells = covering_cells(&bbox(10, 57, 11, 58));
+        assert!(cells.len() <= MAX_COVERING_CELLS);
+        assert!(cells
+            .iter()
+            .any(|cell| "u4pruydqqvj".starts_with(cell.as_str())));
+
+        // both sides of the antimeridian
+        let cells = covering_cells(&bbox(179, 0, -179, 1));
+        assert!(cells.iter().any(|cell| cell.starts_with('x')));
+        assert!(cells.iter().any(|cell| cell.starts_with('8')));
+        assert!(!cells.iter().any(|cell| cell.starts_with('s')));
+    }
+
+    #[test]
+    fn test_ancestor_cells() {
+        let cells = vec!["u4pr".to_string(), "u4px".to_string(), "u4r0".to_string()];
+        assert_eq!(
+            ancestor_cells(&cells),
+            vec!["u", "u4", "u4p", "u4r"]
+                .into_iter()
+                .map(String::from)
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,357 @@
+use cosmwasm_std::Binary;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ContractError;
+
+/// Hash functions accepted as multihash, by multicodec code
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum HashAlgorithm {
+    #[default]
+    Sha2_256,
+    Sha2_512,
+    Keccak256,
+    Blake2b256,
+    Blake3,
+}
+
+impl HashAlgorithm {
+    pub fn code(&self) -> u64 {
+        match self {
+            HashAlgorithm::Sha2_256 => 0x12,
+            HashAlgorithm::Sha2_512 => 0x13,
+            HashAlgorithm::Keccak256 => 0x1b,
+            HashAlgorithm::Blake2b256 => 0xb220,
+            HashAlgorithm::Blake3 => 0x1e,
+        }
+    }
+
+    pub fn from_code(code: u64) -> Option<Self> {
+        match code {
+            0x12 => Some(HashAlgorithm::Sha2_256),
+            0x13 => Some(HashAlgorithm::Sha2_512),
+            0x1b => Some(HashAlgorithm::Keccak256),
+            0xb220 => Some(HashAlgorithm::Blake2b256),
+            0x1e => Some(HashAlgorithm::Blake3),
+            _ => None,
+        }
+    }
+
+    /// digest bytes, blake3 is accepted at its default length only
+    pub fn digest_len(&self) -> usize {
+        match self {
+            HashAlgorithm::Sha2_512 => 64,
+            _ => 32,
+        }
+    }
+}
+
+/// Text encodings of a hash in messages and responses
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum HashEncoding {
+    #[default]
+    Hex,
+    /// standard base64 with padding, as cosmwasm `Binary`
+    Base64,
+    /// base32 (`b`), base58btc (`z`), base16 (`f`) or base64 (`m`) multibase,
+    /// base32 when encoding
+    Multibase,
+}
+
+/// Parses a hex-encoded hash: 32 bytes are a bare sha2-256 digest,
+/// as before multihash support, anything else a multihash.
+/// Returns the algorithm and the digest.
+pub fn parse_hash(data: &str) -> Result<(HashAlgorithm, Vec<u8>), ContractError> {
+    parse_encoded_hash(data, HashEncoding::Hex)
+}
+
+/// Parses a hash in the given encoding, see `parse_hash`
+pub fn parse_encoded_hash(
+    data: &str,
+    encoding: HashEncoding,
+) -> Result<(HashAlgorithm, Vec<u8>), ContractError> {
+    let bin = match encoding {
+        HashEncoding::Hex => hex::decode(data).map_err(|e| e.to_string()),
+        HashEncoding::Base64 => Binary::from_base64(data)
+            .map(Binary::into)
+            .map_err(|e| e.to_string()),
+        HashEncoding::Multibase => {
+            decode_multibase(data).ok_or_else(|| "Invalid multibase".to_string())
+        }
+    }
+    .map_err(ContractError::ParseError)?;
+    if bin.len() == 32 {
+        return Ok((HashAlgorithm::Sha2_256, bin));
+    }
+
+    parse_multihash(&bin).map_err(|e| match e {
+        // report the length as sent
+        ContractError::InvalidHash(_) => ContractError::InvalidHash(data.len()),
+        e => e,
+    })
+}
+
+/// Parses multihash bytes into the algorithm and the digest
+pub fn parse_multihash(bin: &[u8]) -> Result<(HashAlgorithm, Vec<u8>), ContractError> {
+    let invalid = || ContractError::InvalidHash(bin.len() * 2);
+    let (code, rest) = read_varint(bin).ok_or_else(invalid)?;
+    let (len, digest) = read_varint(rest).ok_or_else(invalid)?;
+    let algorithm = HashAlgorithm::from_code(code).ok_or(ContractError::UnsupportedHash(code))?;
+    if len != algorithm.digest_len() as u64 || digest.len() != algorithm.digest_len() {
+        return Err(invalid());
+    }
+    Ok((algorithm, digest.to_vec()))
+}
+
+/// Hex encoding of the hash as accepted by `parse_hash`,
+/// a bare digest for sha2-256 and a multihash otherwise
+pub fn encode_hash(algorithm: HashAlgorithm, digest: &[u8]) -> String {
+    encode_hash_as(algorithm, digest, HashEncoding::Hex)
+}
+
+/// Encoding of the hash as accepted by `parse_encoded_hash`
+pub fn encode_hash_as(algorithm: HashAlgorithm, digest: &[u8], encoding: HashEncoding) -> String {
+    let bin = if algorithm == HashAlgorithm::Sha2_256 {
+        digest.to_vec()
+    } else {
+        let mut bin = write_varint(algorithm.code());
+        bin.extend(write_varint(digest.len() as u64));
+        bin.extend_from_slice(digest);
+        bin
+    };
+    match encoding {
+        HashEncoding::Hex => hex::encode(bin),
+        HashEncoding::Base64 => Binary(bin).to_base64(),
+        HashEncoding::Multibase => format!("b{}", encode_base32(&bin)),
+    }
+}
+
+/// Decodes base32 (`b`), base58btc (`z`), base16 (`f`) or base64 (`m`) multibase
+pub(crate) fn decode_multibase(data: &str) -> Option<Vec<u8>> {
+    let mut chars = data.chars();
+    match chars.next()? {
+        'b' => decode_base32(chars.as_str()),
+        'z' => bs58::decode(chars.as_str()).into_vec().ok(),
+        'f' => hex::decode(chars.as_str()).ok(),
+        'm' => {
+            // multibase base64 is unpadded
+            let data = chars.as_str();
+            let padding = "=".repeat((4 - data.len() % 4) % 4);
+            Binary::from_base64(&format!("{}{}", data, padding))
+                .ok()
+                .map(Binary::into)
+        }
+        _ => None,
+    }
+}
+
+/// Decodes lowercase RFC 4648 base32 without padding
+fn decode_base32(data: &str) -> Option<Vec<u8>> {
+    let mut bin = vec![];
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in data.bytes() {
+        let value = match c {
+            b'a'..=b'z' => c - b'a',
+            b'2'..=b'7' => c - b'2' + 26,
+            _ => return None,
+        };
+        buffer = (buffer << 5) | u32::from(value);
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bin.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    // leftover bits are zero padding
+    if buffer != 0 {
+        return None;
+    }
+    Some(bin)
+}
+
+/// Encodes lowercase RFC 4648 base32 without padding
+pub(crate) fn encode_base32(bin: &[u8]) -> String {
+    let alphabet = b"abcdefghijklmnopqrstuvwxyz234567";
+    let mut data = String::new();
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for byte in bin {
+        buffer = (buffer << 8) | u32::from(*byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            data.push(alphabet[(buffer >> bits) as usize & 31] as char);
+        }
+        buffer &= (1 << bits) - 1;
+    }
+    if bits > 0 {
+        data.push(alphabet[(buffer << (5 - bits)) as usize & 31] as char);
+    }
+    data
+}
+
+/// Reads a minimally encoded unsigned varint, returns it with the remaining bytes
+pub(crate) fn read_varint(bin: &[u8]) -> Option<(u64, &[u8])> {
+    let mut value = 0u64;
+    // at most 9 bytes in multiformats
+    for (i, byte) in bin.iter().enumerate().take(9) {
+        value |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            // a trailing zero byte would give the same value another encoding
+            if i > 0 && *byte == 0 {
+                return None;
+            }
+            return Some((value, &bin[i + 1..]));
+        }
+    }
+    None
+}
+
+fn write_varint(mut value: u64) -> Vec<u8> {
+    let mut bin = vec![];
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            bin.push(byte);
+            return bin;
+        }
+        bin.push(byte | 0x80);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_varint() {
+        assert_eq!(write_varint(0x12), vec![0x12]);
+        assert_eq!(write_varint(0xb220), vec![0xa0, 0xe4, 0x02]);
+        assert_eq!(
+            read_varint(&[0xa0, 0xe4, 0x02, 0x20]),
+            Some((0xb220, &[0x20][..]))
+        );
+        assert_eq!(read_varint(&[0x80]), None);
+        assert_eq!(read_varint(&[0x00]), Some((0, &[][..])));
+        // non-minimal encodings
+        assert_eq!(read_varint(&[0xf0, 0x00]), None);
+        assert_eq!(read_varint(&[0x92, 0x80, 0x00]), None);
+    }
+
+    #[test]
+    fn test_parse_hash() {
+        let digest = vec![7u8; 32];
+
+        // bare digests are sha2-256
+        let (algorithm, parsed) = parse_hash(&hex::encode(&digest)).unwrap();
+        assert_eq!(algorithm, HashAlgorithm::Sha2_256);
+        assert_eq!(parsed, digest);
+
+        for algorithm in [
+            HashAlgorithm::Sha2_256,
+            HashAlgorithm::Keccak256,
+            HashAlgorithm::Blake2b256,
+            HashAlgorithm::Blake3,
+        ] {
+            let mut multihash = write_varint(algorithm.code());
+            multihash.push(32);
+            multihash.extend(&digest);
+            assert_eq!(
+                parse_hash(&hex::encode(multihash)).unwrap(),
+                (algorithm, digest.clone())
+            );
+        }
+
+        let sha512 = encode_hash(HashAlgorithm::Sha2_512, &[9u8; 64]);
+        assert!(sha512.starts_with("1340"));
+        assert_eq!(
+            parse_hash(&sha512).unwrap(),
+            (HashAlgorithm::Sha2_512, vec![9u8; 64])
+        );
+        // sha2-256 is encoded bare, as before multihash
+        assert_eq!(
+            encode_hash(HashAlgorithm::Sha2_256, &digest),
+            hex::encode(&digest)
+        );
+    }
+
+    #[test]
+    fn test_hash_encodings() {
+        let digest = vec![7u8; 32];
+        let blake3 = encode_hash(HashAlgorithm::Blake3, &digest);
+        for (algorithm, hex) in [
+            (HashAlgorithm::Sha2_256, hex::encode(&digest)),
+            (HashAlgorithm::Blake3, blake3),
+        ] {
+            let bin = hex::decode(&hex).unwrap();
+            for (encoding, data) in [
+                (HashEncoding::Hex, hex.clone()),
+                (HashEncoding::Base64, Binary(bin.clone()).to_base64()),
+                (HashEncoding::Multibase, format!("f{}", hex)),
+                (HashEncoding::Multibase, format!("b{}", encode_base32(&bin))),
+                (
+                    HashEncoding::Multibase,
+                    format!("z{}", bs58::encode(&bin).into_string()),
+                ),
+                (
+                    HashEncoding::Multibase,
+                    format!("m{}", Binary(bin.clone()).to_base64().trim_end_matches('=')),
+                ),
+            ] {
+                assert_eq!(
+                    parse_encoded_hash(&data, encoding).unwrap(),
+                    (algorithm, digest.clone())
+                );
+            }
+            for encoding in [
+                HashEncoding::Hex,
+                HashEncoding::Base64,
+                HashEncoding::Multibase,
+            ] {
+                let data = encode_hash_as(algorithm, &digest, encoding);
+                assert_eq!(
+                    parse_encoded_hash(&data, encoding).unwrap(),
+                    (algorithm, digest.clone())
+                );
+            }
+        }
+
+        assert_eq!(
+            // sha2-512 multihash without its digest
+            parse_encoded_hash("E0A=", HashEncoding::Base64),
+            Err(ContractError::InvalidHash(4))
+        );
+        assert!(matches!(
+            parse_encoded_hash("qAAAA", HashEncoding::Multibase),
+            Err(ContractError::ParseError(_))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_hash() {
+        assert_eq!(parse_hash("abcd"), Err(ContractError::InvalidHash(4)));
+        // digest shorter than its length
+        assert_eq!(
+            parse_hash(&format!("1b20{}", "00".repeat(31))),
+            Err(ContractError::InvalidHash(66))
+        );
+        // sha2-512 needs 64 bytes
+        assert_eq!(
+            parse_hash(&format!("1320{}", "00".repeat(32))),
+            Err(ContractError::InvalidHash(68))
+        );
+        assert_eq!(
+            parse_hash(&format!("1120{}", "00".repeat(32))),
+            Err(ContractError::UnsupportedHash(0x11))
+        );
+        assert!(matches!(
+            parse_hash("bu115h17"),
+            Err(ContractError::ParseError(_))
+        ));
+    }
+}
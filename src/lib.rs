@@ -1,5 +1,9 @@
+pub mod cid;
 pub mod contract;
 mod error;
+pub mod geo;
+pub mod hash;
+pub mod merkle;
 pub mod msg;
 pub mod state;
 
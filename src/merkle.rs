@@ -0,0 +1,106 @@
+use sha2::{Digest, Sha256};
+
+/// Prefixes of leaf and inner node hashes, so an inner node can't pass for a leaf
+const LEAF: u8 = 0x00;
+const NODE: u8 = 0x01;
+
+/// Leaf node of a geodata hash
+pub fn hash_leaf(leaf: &[u8]) -> Vec<u8> {
+    Sha256::new()
+        .chain_update([LEAF])
+        .chain_update(leaf)
+        .finalize()
+        .to_vec()
+}
+
+/// Parent of two nodes, hashed in sorted order so proofs don't need positions
+pub fn hash_pair(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    Sha256::new()
+        .chain_update([NODE])
+        .chain_update(first)
+        .chain_update(second)
+        .finalize()
+        .to_vec()
+}
+
+/// Root reached from the leaf through the sibling nodes of the proof
+pub fn compute_root(leaf: &[u8], proof: &[Vec<u8>]) -> Vec<u8> {
+    proof
+        .iter()
+        .fold(hash_leaf(leaf), |node, sibling| hash_pair(&node, sibling))
+}
+
+/// Root of the leaves, the geodata hashes, carrying an odd node up a level.
+/// None without leaves.
+pub fn merkle_root(leaves: &[Vec<u8>]) -> Option<Vec<u8>> {
+    let mut level: Vec<_> = leaves.iter().map(|leaf| hash_leaf(leaf)).collect();
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => hash_pair(a, b),
+                [a] => a.clone(),
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+    level.pop()
+}
+
+/// Most sibling nodes in a proof of a tree of the leaves, the tree depth
+pub fn max_proof_len(leaves: u64) -> usize {
+    (u64::BITS - leaves.saturating_sub(1).leading_zeros()) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaf(n: u8) -> Vec<u8> {
+        Sha256::digest([n]).to_vec()
+    }
+
+    #[test]
+    fn test_hash_pair_sorted() {
+        assert_eq!(hash_pair(&leaf(1), &leaf(2)), hash_pair(&leaf(2), &leaf(1)));
+    }
+
+    #[test]
+    fn test_merkle_root() {
+        assert_eq!(None, merkle_root(&[]));
+        assert_eq!(Some(hash_leaf(&leaf(1))), merkle_root(&[leaf(1)]));
+
+        let leaves: Vec<_> = (0..3).map(leaf).collect();
+        let nodes: Vec<_> = leaves.iter().map(|leaf| hash_leaf(leaf)).collect();
+        let root = merkle_root(&leaves).unwrap();
+        let ab = hash_pair(&nodes[0], &nodes[1]);
+        assert_eq!(root, hash_pair(&ab, &nodes[2]));
+
+        // every leaf proves against the root
+        assert_eq!(
+            root,
+            compute_root(&leaf(0), &[nodes[1].clone(), nodes[2].clone()])
+        );
+        assert_eq!(
+            root,
+            compute_root(&leaf(1), &[nodes[0].clone(), nodes[2].clone()])
+        );
+        assert_eq!(root, compute_root(&leaf(2), std::slice::from_ref(&ab)));
+        assert_ne!(
+            root,
+            compute_root(&leaf(3), &[nodes[1].clone(), nodes[2].clone()])
+        );
+        // an inner node isn't a leaf
+        assert_ne!(root, compute_root(&ab, &[nodes[2].clone()]));
+    }
+
+    #[test]
+    fn test_max_proof_len() {
+        assert_eq!(0, max_proof_len(1));
+        assert_eq!(1, max_proof_len(2));
+        assert_eq!(2, max_proof_len(3));
+        assert_eq!(2, max_proof_len(4));
+        assert_eq!(3, max_proof_len(5));
+    }
+}
@@ -1,15 +1,18 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::Timestamp;
+use cosmwasm_std::{Binary, Coin, Timestamp};
 
-use crate::state::Validation;
+use crate::geo::BoundingBox;
+use crate::hash::HashEncoding;
+use crate::state::{
+    AnchorKind, AnchorStatus, DisputeState, KeyAlgorithm, PublicKey, RejectionReason, Revocation,
+    ValidationSignature,
+};
 
-/// TODO: implement access control based on admins and users
-/// admins can instatiate and modify access lists, if mutable
-/// admins can execute
-/// users can query
-/// see cw1-whitelist
+/// Access control, as cw1-whitelist.
+/// Admins and users can create and validate anchors,
+/// admins can modify the access lists while mutable.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     pub admins: Vec<String>,
@@ -17,11 +20,116 @@ pub struct InstantiateMsg {
     pub mutable: bool,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {
+    /// replaces the access lists, required when migrating from a version without them
+    pub config: Option<InstantiateMsg>,
+    /// anchors migrated right away, the rest by `MigrateAnchors`
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
     Create(CreateMsg),
     Validate(ValidateMsg),
+    /// Creates all the anchors or none, up to the max batch size.
+    BatchCreate(Vec<CreateMsg>),
+    /// Validates all the anchors or none, up to the max batch size.
+    BatchValidate(Vec<ValidateMsg>),
+    /// Records a dissenting attestation with the hash the source computed,
+    /// once per source.
+    Reject {
+        id: String,
+        hash: String,
+        /// encoding of `hash`, hex unless set
+        #[serde(default)]
+        encoding: HashEncoding,
+        reason: RejectionReason,
+    },
+    /// Replaces the hash of the anchor, keeping the previous one as a revision.
+    /// Only the source or admins.
+    Revise(ReviseMsg),
+    /// Retracts the anchor, keeping it as a tombstone. Only the source or admins.
+    Revoke {
+        id: String,
+        reason: String,
+    },
+    /// Opens a challenge against the anchor, or one of its validations, paying the
+    /// configured bond. One open challenge per anchor.
+    Challenge {
+        id: String,
+        validation: Option<u64>,
+        reason: String,
+    },
+    /// Resolves an open challenge, refunding the bond if overturned. Only admins.
+    ResolveChallenge {
+        challenge_id: u64,
+        overturn: bool,
+    },
+    /// Votes on an open challenge with the validator weight,
+    /// resolving it once a side reaches the quorum.
+    VoteChallenge {
+        challenge_id: u64,
+        overturn: bool,
+    },
+    /// Replaces the admin list, only while mutable.
+    UpdateAdmins {
+        admins: Vec<String>,
+    },
+    /// Replaces the user list, only while mutable.
+    UpdateUsers {
+        users: Vec<String>,
+    },
+    /// Registers the public key the sender signs validations with,
+    /// replaces any previous key. Open to every source that may validate.
+    RegisterKey {
+        algorithm: KeyAlgorithm,
+        key: Binary,
+        /// signature of `key_registration_message`, proving the sender holds the key
+        signature: Binary,
+    },
+    /// Sets the seconds after which a source may validate the same anchor again,
+    /// `None` allows a single validation per source. Only while mutable.
+    SetRevalidationInterval {
+        interval: Option<u64>,
+    },
+    /// Adds and removes registered validators, only while mutable.
+    UpdateValidators {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    /// Sets the weight of distinct validators that verifies an anchor,
+    /// `None` disables verification. Only while mutable.
+    SetQuorum {
+        quorum: Option<u64>,
+    },
+    /// Weights validators by membership of a cw4 group instead of the registered
+    /// validators, `None` goes back to them. Only while mutable.
+    SetGroup {
+        group: Option<String>,
+    },
+    /// Sets the messages allowed in a batch, `None` restores the default.
+    /// Only while mutable.
+    SetMaxBatchSize {
+        size: Option<u32>,
+    },
+    /// Sets the funds required to open a challenge and who receives the bonds of
+    /// upheld challenges, required with a bond. `None` closes challenges.
+    /// Only while mutable.
+    SetChallengeBond {
+        bond: Option<Coin>,
+        #[serde(default)]
+        treasury: Option<String>,
+    },
+    /// Makes the access lists immutable, can't be undone.
+    Freeze {},
+    /// Migrates the next anchors after `migrate`, the only message accepted until
+    /// every anchor is migrated. Open to anyone.
+    MigrateAnchors {
+        limit: Option<u32>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -30,10 +138,25 @@ pub struct CreateMsg {
     pub id: String,
     /// hex of account objectid
     pub account: String,
-    /// hex-encoded hash of geodata (must be 32*2 = 64 chars)
+    /// hash of geodata: a sha2-256 digest or a multihash, see `hash`
     pub hash: String,
+    /// encoding of `hash`, hex unless set
+    #[serde(default)]
+    pub encoding: HashEncoding,
     /// geodata created
     pub created: Timestamp,
+    /// a single object unless set
+    #[serde(default)]
+    pub kind: AnchorKind,
+    /// IPFS CID of the geodata, CIDv0 or CIDv1 in base32, base58btc or base16
+    #[serde(default)]
+    pub cid: Option<String>,
+    /// extent of the geodata, see `geo`
+    #[serde(default)]
+    pub bounding_box: Option<BoundingBox>,
+    /// location of the geodata, a geohash of 1 to 12 characters
+    #[serde(default)]
+    pub geohash: Option<String>,
 }
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ValidateMsg {
@@ -41,23 +164,246 @@ pub struct ValidateMsg {
     pub id: String,
     /// hex of account objectid
     pub account: String,
-    /// hex-encoded hash of geodata (must be 32*2 = 64 chars)
+    /// hash of geodata: a sha2-256 digest or a multihash, see `hash`
     pub hash: String,
+    /// encoding of `hash`, hex unless set
+    #[serde(default)]
+    pub encoding: HashEncoding,
     /// validation created
     pub created: Timestamp,
+    /// signature of `validation_message`, required once the sender registered a key
+    #[serde(default)]
+    pub signature: Option<Binary>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReviseMsg {
+    /// hex of geodata objectid (PK)
+    pub id: String,
+    /// hash of the corrected geodata: a sha2-256 digest or a multihash, see `hash`
+    pub new_hash: String,
+    /// encoding of `new_hash`, hex unless set
+    #[serde(default)]
+    pub encoding: HashEncoding,
+    /// corrected geodata created
+    pub created: Timestamp,
+    /// CID of the corrected geodata, the previous one is cleared if not set
+    #[serde(default)]
+    pub cid: Option<String>,
+    /// leaf count of the revised merkle root, required for merkle roots only
+    #[serde(default)]
+    pub leaves: Option<u64>,
+}
+
+/// The message validators sign: `validate`, the contract and sender addresses,
+/// each prefixed with its length as a byte, id, hash bytes and created nanos (big-endian).
+/// secp256k1 keys sign its sha256, ed25519 keys sign it as is.
+pub fn validation_message(
+    contract: &str,
+    sender: &str,
+    id: &str,
+    hash: &[u8],
+    created: Timestamp,
+) -> Vec<u8> {
+    let mut message = b"validate".to_vec();
+    push_address(&mut message, contract);
+    push_address(&mut message, sender);
+    message.extend_from_slice(id.as_bytes());
+    message.extend_from_slice(hash);
+    message.extend_from_slice(&created.nanos().to_be_bytes());
+    message
+}
+
+/// The message signed to register a key: `register_key`, the contract and sender
+/// addresses, each prefixed with its length as a byte, and the key.
+/// Signed like `validation_message`.
+pub fn key_registration_message(contract: &str, sender: &str, key: &[u8]) -> Vec<u8> {
+    let mut message = b"register_key".to_vec();
+    push_address(&mut message, contract);
+    push_address(&mut message, sender);
+    message.extend_from_slice(key);
+    message
+}
+
+fn push_address(message: &mut Vec<u8>, address: &str) {
+    message.push(address.len() as u8);
+    message.extend_from_slice(address.as_bytes());
 }
 
 pub fn is_valid_id(id: &str) -> bool {
-    id.as_bytes().len() == 24
+    id.len() == 24
 }
 
-/// TODO: add Valid {id: String, hash: String}
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     /// Returns the details of the anchor, error if not created.
     /// Return type: DetailsResponse.
-    Details { id: String },
+    Details {
+        id: String,
+        /// encoding of the returned hash, hex unless set
+        #[serde(default)]
+        encoding: HashEncoding,
+    },
+    /// Checks the hash against the anchor, error if the hash is malformed.
+    /// Return type: ValidResponse.
+    Valid {
+        id: String,
+        hash: String,
+        /// encoding of `hash`, hex unless set
+        #[serde(default)]
+        encoding: HashEncoding,
+    },
+    /// Lists validations of the anchor in order, paginated by `start_after` index.
+    /// Return type: ValidationsResponse.
+    Validations {
+        id: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        /// encoding of the returned hashes, hex unless set
+        #[serde(default)]
+        encoding: HashEncoding,
+    },
+    /// Lists rejections of the anchor in source order, paginated by `start_after` source.
+    /// Return type: RejectionsResponse.
+    Rejections {
+        id: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+        /// encoding of the returned hashes, hex unless set
+        #[serde(default)]
+        encoding: HashEncoding,
+    },
+    /// Lists the revisions of the anchor in order, ending with the current one.
+    /// Return type: HistoryResponse.
+    History {
+        id: String,
+        /// encoding of the returned hashes, hex unless set
+        #[serde(default)]
+        encoding: HashEncoding,
+    },
+    /// Returns the challenge, error if not opened.
+    /// Return type: ChallengeResponse.
+    Challenge { challenge_id: u64 },
+    /// Lists challenges of the anchor in order, paginated by `start_after` challenge id.
+    /// Return type: ChallengesResponse.
+    Challenges {
+        id: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns the details of the anchor of the CID, in any of its encodings.
+    /// Return type: DetailsResponse.
+    AnchorByCid {
+        cid: String,
+        /// encoding of the returned hash, hex unless set
+        #[serde(default)]
+        encoding: HashEncoding,
+    },
+    /// Checks that the leaf hash is in the merkle root anchor, recomputing the root
+    /// from the sibling nodes of the proof, leaf to root, see `merkle::compute_root`.
+    /// Error if the anchor isn't a merkle root.
+    /// Return type: InclusionResponse.
+    VerifyInclusion {
+        root_id: String,
+        leaf_hash: String,
+        proof: Vec<String>,
+        /// encoding of `leaf_hash` and the proof nodes, hex unless set
+        #[serde(default)]
+        encoding: HashEncoding,
+    },
+    /// Lists anchors in id order, paginated by `start_after` id.
+    /// Return type: ListAnchorsResponse.
+    ListAnchors {
+        start_after: Option<String>,
+        limit: Option<u32>,
+        /// encoding of the returned hashes, hex unless set
+        #[serde(default)]
+        encoding: HashEncoding,
+    },
+    /// Lists anchors of an account objectid in id order, paginated by `start_after` id.
+    /// Return type: ListAnchorsResponse.
+    AnchorsByAccount {
+        account: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+        /// encoding of the returned hashes, hex unless set
+        #[serde(default)]
+        encoding: HashEncoding,
+    },
+    /// Lists anchors submitted by an address in id order, paginated by `start_after` id.
+    /// Return type: ListAnchorsResponse.
+    AnchorsBySource {
+        source: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+        /// encoding of the returned hashes, hex unless set
+        #[serde(default)]
+        encoding: HashEncoding,
+    },
+    /// Lists anchors of the hash and its algorithm in id order, paginated by `start_after` id.
+    /// Error if the hash is malformed.
+    /// Return type: ListAnchorsResponse.
+    AnchorsByHash {
+        hash: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+        /// encoding of `hash` and the returned hashes, hex unless set
+        #[serde(default)]
+        encoding: HashEncoding,
+    },
+    /// Lists anchors created in [from, to) in created order,
+    /// paginated by `start_after` id.
+    /// Return type: ListAnchorsResponse.
+    AnchorsCreatedBetween {
+        from: Timestamp,
+        to: Timestamp,
+        start_after: Option<String>,
+        limit: Option<u32>,
+        /// encoding of the returned hashes, hex unless set
+        #[serde(default)]
+        encoding: HashEncoding,
+    },
+    /// Lists anchors located within the geohash prefix, all located anchors if empty,
+    /// in geohash order, paginated by `start_after` id.
+    /// Return type: ListAnchorsResponse.
+    AnchorsInGeohash {
+        prefix: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+        /// encoding of the returned hashes, hex unless set
+        #[serde(default)]
+        encoding: HashEncoding,
+    },
+    /// Lists anchors whose geohash cell center lies in the box, scanning the covering
+    /// geohash cells in order, paginated by `start_after` id. A page scans a bounded number
+    /// of anchors, so it may come short before the end; resume after its `next` id.
+    /// Anchors located more coarsely than the covering cells, see `geo::covering_cells`,
+    /// are found by the geohashes holding those cells.
+    /// Return type: AnchorsInBoundingBoxResponse.
+    AnchorsInBoundingBox {
+        bounding_box: BoundingBox,
+        start_after: Option<String>,
+        limit: Option<u32>,
+        /// encoding of the returned hashes, hex unless set
+        #[serde(default)]
+        encoding: HashEncoding,
+    },
+    /// Returns the public key registered for the address.
+    /// Return type: PublicKeyResponse.
+    PublicKey { address: String },
+    /// Returns the verification status of the anchor, error if not created.
+    /// Return type: StatusResponse.
+    Status { id: String },
+    /// Lists registered validators in address order, paginated by `start_after` address.
+    /// Return type: ValidatorsResponse.
+    Validators {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns the access lists.
+    /// Return type: ConfigResponse.
+    Config {},
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -68,5 +414,181 @@ pub struct DetailsResponse {
     pub hash: String,
     pub source: String,
     pub created: Timestamp,
-    pub validations: Vec<Validation>,
+    /// number of validations of the anchor
+    pub validations: u64,
+    /// number of rejections of the anchor
+    pub rejections: u64,
+    pub status: AnchorStatus,
+    /// state of the latest challenge of the anchor itself, if ever challenged
+    pub dispute: Option<DisputeState>,
+    /// latest challenge of one of its validations, if any
+    pub validation_dispute: Option<ValidationDispute>,
+    pub revoked: Option<Revocation>,
+    /// number of previous revisions
+    pub revision: u32,
+    pub kind: AnchorKind,
+    pub cid: Option<String>,
+    pub bounding_box: Option<BoundingBox>,
+    pub geohash: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ValidationDispute {
+    /// index of the challenged validation
+    pub validation: u64,
+    pub state: DisputeState,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct StatusResponse {
+    pub status: AnchorStatus,
+    /// weight of the distinct validators that validated the anchor
+    pub weight: u64,
+    pub quorum: Option<u64>,
+    /// when a validation crossed the quorum, none while pending
+    /// or if verified by lowering the quorum
+    pub verified: Option<Timestamp>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ValidatorsResponse {
+    pub validators: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ValidationInfo {
+    /// position in the validations of the anchor
+    pub index: u64,
+    pub account: String,
+    pub hash: String,
+    pub source: String,
+    pub created: Timestamp,
+    pub signature: Option<ValidationSignature>,
+    /// revision of the anchor validated
+    pub revision: u32,
+    /// state of the latest challenge of the validation, if ever challenged
+    pub dispute: Option<DisputeState>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ValidationsResponse {
+    pub validations: Vec<ValidationInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RejectionInfo {
+    pub source: String,
+    /// hash computed by the source
+    pub hash: String,
+    pub reason: RejectionReason,
+    pub created: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RejectionsResponse {
+    pub rejections: Vec<RejectionInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RevisionInfo {
+    pub revision: u32,
+    pub hash: String,
+    pub kind: AnchorKind,
+    pub cid: Option<String>,
+    pub created: Timestamp,
+    /// when the next revision replaced it, `None` for the current one
+    pub superseded: Option<Timestamp>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct HistoryResponse {
+    pub revisions: Vec<RevisionInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ChallengeResponse {
+    pub challenge_id: u64,
+    /// geodata id of the challenged anchor
+    pub anchor_id: String,
+    /// index of the challenged validation, the anchor itself if not set
+    pub validation: Option<u64>,
+    pub challenger: String,
+    pub bond: Coin,
+    /// receives the bond if upheld
+    pub treasury: String,
+    pub reason: String,
+    pub created: Timestamp,
+    pub state: DisputeState,
+    pub uphold_weight: u64,
+    pub overturn_weight: u64,
+    pub resolved: Option<Timestamp>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ChallengesResponse {
+    pub challenges: Vec<ChallengeResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct AnchorSummary {
+    /// geodata id (PK)
+    pub id: String,
+    pub account: String,
+    pub hash: String,
+    pub source: String,
+    pub created: Timestamp,
+    /// number of validations of the anchor
+    pub validations: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ListAnchorsResponse {
+    pub anchors: Vec<AnchorSummary>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct AnchorsInBoundingBoxResponse {
+    pub anchors: Vec<AnchorSummary>,
+    /// last anchor scanned, `start_after` of the next page, none once the box is scanned
+    pub next: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ValidResponse {
+    /// anchor exists for the id
+    pub exists: bool,
+    /// anchored hash equals the given hash
+    pub hash_matches: bool,
+    /// number of validations of the anchor
+    pub validations: u64,
+    /// geodata created, if the anchor exists
+    pub created: Option<Timestamp>,
+    /// anchor was revoked, it shouldn't be relied on
+    pub revoked: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct InclusionResponse {
+    /// proof leads from the leaf to the anchored root
+    pub included: bool,
+    /// root was revoked, it shouldn't be relied on
+    pub revoked: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PublicKeyResponse {
+    pub public_key: Option<PublicKey>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ConfigResponse {
+    pub admins: Vec<String>,
+    pub users: Vec<String>,
+    pub mutable: bool,
+    pub revalidation_interval: Option<u64>,
+    pub quorum: Option<u64>,
+    pub group: Option<String>,
+    pub challenge_bond: Option<Coin>,
+    pub treasury: Option<String>,
+    pub max_batch_size: u32,
 }
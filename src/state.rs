@@ -1,27 +1,547 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Binary, Order, StdResult, Storage, Timestamp};
-use cw_storage_plus::{Bound, Map};
+use cosmwasm_std::{Addr, Binary, Coin, Empty, Order, StdError, StdResult, Storage, Timestamp};
+use cw_storage_plus::{Bound, Index, IndexList, IndexedMap, Item, Map, MultiIndex};
+
+use crate::geo::BoundingBox;
+use crate::hash::HashAlgorithm;
+
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, JsonSchema, Debug)]
+pub struct Config {
+    pub admins: Vec<Addr>,
+    pub users: Vec<Addr>,
+    pub mutable: bool,
+    /// seconds after which a source may validate the same anchor again,
+    /// never if not set
+    #[serde(default)]
+    pub revalidation_interval: Option<u64>,
+    /// weight of distinct validators needed to verify an anchor,
+    /// anchors are never verified if not set
+    #[serde(default)]
+    pub quorum: Option<u64>,
+    /// cw4 group weighting validators, instead of the registered validators
+    #[serde(default)]
+    pub group: Option<Addr>,
+    /// funds required to open a challenge, challenges are closed if not set
+    #[serde(default)]
+    pub challenge_bond: Option<Coin>,
+    /// receives the bonds of upheld challenges, set with the bond
+    #[serde(default)]
+    pub treasury: Option<Addr>,
+    /// messages allowed in a batch, a default if not set
+    #[serde(default)]
+    pub max_batch_size: Option<u32>,
+}
+
+impl Config {
+    /// returns true if the address is a registered admin
+    pub fn is_admin(&self, addr: impl AsRef<str>) -> bool {
+        let addr = addr.as_ref();
+        self.admins.iter().any(|a| a.as_ref() == addr)
+    }
+
+    /// returns true if the address is a registered user
+    pub fn is_user(&self, addr: impl AsRef<str>) -> bool {
+        let addr = addr.as_ref();
+        self.users.iter().any(|a| a.as_ref() == addr)
+    }
+
+    /// returns true if the address can modify the access lists
+    pub fn can_modify(&self, addr: impl AsRef<str>) -> bool {
+        self.mutable && self.is_admin(addr)
+    }
+
+    /// returns true if the address may create and validate anchors
+    pub fn can_execute(&self, addr: impl AsRef<str>) -> bool {
+        self.is_admin(&addr) || self.is_user(&addr)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyAlgorithm {
+    /// signs the sha256 of the message, 33 or 65 bytes key
+    Secp256k1,
+    /// signs the message, 32 bytes key
+    Ed25519,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct PublicKey {
+    pub algorithm: KeyAlgorithm,
+    pub key: Binary,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ValidationSignature {
+    /// key of the source when validating
+    pub public_key: PublicKey,
+    /// signature of `msg::validation_message`
+    pub signature: Binary,
+}
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct Validation {
     pub account: String,
     pub hash: Binary,
+    #[serde(default)]
+    pub algorithm: HashAlgorithm,
     pub source: Addr,
     pub created: Timestamp,
+    #[serde(default)]
+    pub signature: Option<ValidationSignature>,
+    /// revision of the anchor validated
+    #[serde(default)]
+    pub revision: u32,
+    /// weight counted towards the tally of the revision, removed if overturned
+    #[serde(default)]
+    pub weight: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum RejectionReason {
+    /// the data doesn't hash to the anchored hash
+    HashMismatch,
+    /// the data couldn't be read
+    CorruptData,
+    /// the data couldn't be retrieved
+    Unavailable,
+    Other(String),
+}
+
+/// Dissenting attestation of an anchor
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Rejection {
+    /// hash computed by the source
+    pub hash: Binary,
+    #[serde(default)]
+    pub algorithm: HashAlgorithm,
+    pub reason: RejectionReason,
+    /// block time of the rejection
+    pub created: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum AnchorStatus {
+    /// quorum of validators not reached yet
+    Pending,
+    /// quorum of validators reached
+    Verified,
+}
+
+/// Weight of the validators that validated an anchor
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, JsonSchema, Debug)]
+pub struct Tally {
+    /// revision of the anchor tallied, earlier revisions are discarded
+    #[serde(default)]
+    pub revision: u32,
+    pub weight: u64,
+    /// when a validation last crossed the quorum in force
+    pub verified: Option<Timestamp>,
+}
+
+impl Tally {
+    /// Verified while the weight meets the quorum in force,
+    /// so a changed quorum applies to existing anchors too
+    pub fn status(&self, quorum: Option<u64>) -> AnchorStatus {
+        match quorum {
+            Some(quorum) if self.weight >= quorum => AnchorStatus::Verified,
+            _ => AnchorStatus::Pending,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum DisputeState {
+    /// challenge open
+    Disputed,
+    /// challenge rejected, the anchor stands and the bond is forfeited
+    Upheld,
+    /// challenge accepted, the bond is refunded
+    Overturned,
+}
+
+/// Challenge against an anchor or one of its validations
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Challenge {
+    pub anchor_id: String,
+    /// index of the challenged validation, the anchor itself if not set
+    pub validation: Option<u64>,
+    pub challenger: Addr,
+    pub bond: Coin,
+    /// receives the bond if upheld, the treasury when the challenge was opened
+    pub treasury: Addr,
+    pub reason: String,
+    /// block time of the challenge
+    pub created: Timestamp,
+    pub state: DisputeState,
+    /// weight of the validators voting to uphold the anchor
+    pub uphold_weight: u64,
+    /// weight of the validators voting to overturn it
+    pub overturn_weight: u64,
+    /// block time of the resolution
+    pub resolved: Option<Timestamp>,
+}
+
+/// Retraction of an anchor, kept as a tombstone
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Revocation {
+    /// source or admin that revoked the anchor
+    pub by: Addr,
+    pub reason: String,
+    /// block time of the revocation
+    pub created: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum AnchorKind {
+    /// hash of a geodata object
+    #[default]
+    Object,
+    /// sha256 sorted-pair merkle root of the hashes of geodata objects,
+    /// see `merkle`
+    MerkleRoot { leaves: u64 },
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct Anchor {
     pub account: String,
+    /// digest of the hash
     pub hash: Binary,
+    /// sha2-256 for anchors from before multihash support
+    #[serde(default)]
+    pub algorithm: HashAlgorithm,
     pub source: Addr,
     pub created: Timestamp,
-    pub validations: Vec<Validation>,
+    #[serde(default)]
+    pub revoked: Option<Revocation>,
+    /// number of previous revisions, see REVISIONS
+    #[serde(default)]
+    pub revision: u32,
+    #[serde(default)]
+    pub kind: AnchorKind,
+    /// IPFS CID of the geodata, as given
+    #[serde(default)]
+    pub cid: Option<String>,
+    #[serde(default)]
+    pub bounding_box: Option<BoundingBox>,
+    /// location of the geodata, see GEOHASHES
+    #[serde(default)]
+    pub geohash: Option<String>,
+}
+
+/// Superseded hash of a revised anchor
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Revision {
+    pub hash: Binary,
+    #[serde(default)]
+    pub algorithm: HashAlgorithm,
+    #[serde(default)]
+    pub kind: AnchorKind,
+    /// CID of the superseded geodata, unindexed by the revision
+    #[serde(default)]
+    pub cid: Option<String>,
+    pub created: Timestamp,
+    /// block time of the revision replacing it
+    pub superseded: Timestamp,
+    /// source or admin that replaced it
+    pub by: Addr,
+}
+
+pub const CONFIG: Item<Config> = Item::new("config");
+pub struct AnchorIndexes<'a> {
+    /// anchors by account objectid
+    pub account: MultiIndex<'a, String, Anchor, String>,
+    /// anchors by submitting address
+    pub source: MultiIndex<'a, Addr, Anchor, String>,
+    /// anchors by hash of geodata, (multicodec code of the algorithm, digest)
+    pub hash: MultiIndex<'a, (u64, Vec<u8>), Anchor, String>,
+    /// anchors by geodata created, in nanos
+    pub created: MultiIndex<'a, u64, Anchor, String>,
+}
+
+impl<'a> IndexList<Anchor> for AnchorIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Anchor>> + '_> {
+        let v: Vec<&dyn Index<Anchor>> =
+            vec![&self.account, &self.source, &self.hash, &self.created];
+        Box::new(v.into_iter())
+    }
+}
+
+/// Anchors by geodata id, with secondary indexes
+pub fn anchors<'a>() -> IndexedMap<'a, &'a str, Anchor, AnchorIndexes<'a>> {
+    let indexes = AnchorIndexes {
+        account: MultiIndex::new(
+            |a: &Anchor| a.account.clone(),
+            "anchors",
+            "anchors__account",
+        ),
+        source: MultiIndex::new(|a: &Anchor| a.source.clone(), "anchors", "anchors__source"),
+        hash: MultiIndex::new(
+            |a: &Anchor| (a.algorithm.code(), a.hash.to_vec()),
+            "anchors",
+            "anchors__hash",
+        ),
+        created: MultiIndex::new(
+            |a: &Anchor| a.created.nanos(),
+            "anchors",
+            "anchors__created",
+        ),
+    };
+    IndexedMap::new("anchors", indexes)
+}
+
+/// Validations by (geodata id, index), kept apart from the anchor so
+/// validating doesn't rewrite a growing list
+pub const VALIDATIONS: Map<(&str, u64), Validation> = Map::new("validations");
+/// Number of validations by geodata id, the next validation index
+pub const VALIDATION_COUNTS: Map<&str, u64> = Map::new("validation_counts");
+/// Public keys validators sign with, by address
+pub const PUBLIC_KEYS: Map<&Addr, PublicKey> = Map::new("public_keys");
+/// Validators counted towards the quorum with weight 1, registered by admins.
+/// Unused while a cw4 group is configured.
+pub const VALIDATORS: Map<&Addr, Empty> = Map::new("validators");
+/// Tally of validator weights by geodata id
+pub const TALLIES: Map<&str, Tally> = Map::new("tallies");
+/// Geodata ids by binary CIDv1, a CID is anchored once
+pub const CIDS: Map<&[u8], String> = Map::new("cids");
+/// Superseded revisions by (geodata id, revision), in order
+pub const REVISIONS: Map<(&str, u32), Revision> = Map::new("revisions");
+/// Revision and time of the last validation by (geodata id, source)
+pub const VALIDATED: Map<(&str, &Addr), (u32, Timestamp)> = Map::new("validated");
+/// Located geodata by raw `geohash:id` keys, see `geohash_key`.
+/// Composite keys are length-prefixed, which would rule out ranging over a geohash prefix.
+pub const GEOHASHES: Map<&[u8], Empty> = Map::new("geohashes");
+
+/// Rejections by (geodata id, source), one per source
+pub const REJECTIONS: Map<(&str, &Addr), Rejection> = Map::new("rejections");
+/// Number of rejections by geodata id
+pub const REJECTION_COUNTS: Map<&str, u64> = Map::new("rejection_counts");
+
+/// Challenges by sequence id
+pub const CHALLENGES: Map<u64, Challenge> = Map::new("challenges");
+/// Number of challenges, the next challenge id
+pub const CHALLENGE_COUNT: Item<u64> = Item::new("challenge_count");
+/// Challenge ids by (geodata id, challenge id)
+pub const ANCHOR_CHALLENGES: Map<(&str, u64), Empty> = Map::new("anchor_challenges");
+/// Latest challenge id by (geodata id, validation index)
+pub const VALIDATION_CHALLENGES: Map<(&str, u64), u64> = Map::new("validation_challenges");
+/// Votes by (challenge id, validator), true to overturn
+pub const CHALLENGE_VOTES: Map<(u64, &Addr), bool> = Map::new("challenge_votes");
+
+/// Stores a new challenge, returns its id
+pub fn add_challenge(storage: &mut dyn Storage, challenge: &Challenge) -> StdResult<u64> {
+    let id = CHALLENGE_COUNT.may_load(storage)?.unwrap_or_default();
+    CHALLENGES.save(storage, id, challenge)?;
+    ANCHOR_CHALLENGES.save(storage, (&challenge.anchor_id, id), &Empty {})?;
+    if let Some(index) = challenge.validation {
+        VALIDATION_CHALLENGES.save(storage, (&challenge.anchor_id, index), &id)?;
+    }
+    CHALLENGE_COUNT.save(storage, &(id + 1))?;
+    Ok(id)
+}
+
+/// Latest challenge of the anchor or its validations, the only one that may be open
+pub fn latest_challenge(storage: &dyn Storage, id: &str) -> StdResult<Option<(u64, Challenge)>> {
+    latest_challenge_where(storage, id, |_| true)
+}
+
+/// Latest challenge against the anchor itself, or against one of its validations,
+/// which decides the dispute state of either
+pub fn latest_challenge_against(
+    storage: &dyn Storage,
+    id: &str,
+    validations: bool,
+) -> StdResult<Option<(u64, Challenge)>> {
+    latest_challenge_where(storage, id, |challenge| {
+        challenge.validation.is_some() == validations
+    })
+}
+
+fn latest_challenge_where(
+    storage: &dyn Storage,
+    id: &str,
+    filter: impl Fn(&Challenge) -> bool,
+) -> StdResult<Option<(u64, Challenge)>> {
+    ANCHOR_CHALLENGES
+        .prefix(id)
+        .keys(storage, None, None, Order::Descending)
+        .map(|challenge_id| {
+            let challenge_id = challenge_id?;
+            Ok((challenge_id, CHALLENGES.load(storage, challenge_id)?))
+        })
+        // errors stop the search
+        .find(|item| match item {
+            Ok((_, challenge)) => filter(challenge),
+            Err(_) => true,
+        })
+        .transpose()
+}
+
+/// Dispute state of the validation, from its latest challenge
+pub fn validation_dispute(
+    storage: &dyn Storage,
+    id: &str,
+    index: u64,
+) -> StdResult<Option<DisputeState>> {
+    VALIDATION_CHALLENGES
+        .may_load(storage, (id, index))?
+        .map(|challenge_id| Ok(CHALLENGES.load(storage, challenge_id)?.state))
+        .transpose()
+}
+
+/// Stores a validation of the anchor, returns its index
+pub fn add_validation(
+    storage: &mut dyn Storage,
+    id: &str,
+    validation: &Validation,
+) -> StdResult<u64> {
+    let index = validation_count(storage, id)?;
+    VALIDATIONS.save(storage, (id, index), validation)?;
+    VALIDATION_COUNTS.save(storage, id, &(index + 1))?;
+    Ok(index)
+}
+
+pub fn validation_count(storage: &dyn Storage, id: &str) -> StdResult<u64> {
+    Ok(VALIDATION_COUNTS.may_load(storage, id)?.unwrap_or_default())
+}
+
+/// Stores the first rejection of the anchor by the source
+pub fn add_rejection(
+    storage: &mut dyn Storage,
+    id: &str,
+    source: &Addr,
+    rejection: &Rejection,
+) -> StdResult<()> {
+    let count = rejection_count(storage, id)?;
+    REJECTION_COUNTS.save(storage, id, &(count + 1))?;
+    REJECTIONS.save(storage, (id, source), rejection)
+}
+
+pub fn rejection_count(storage: &dyn Storage, id: &str) -> StdResult<u64> {
+    Ok(REJECTION_COUNTS.may_load(storage, id)?.unwrap_or_default())
+}
+
+/// Anchor as stored before validations had their own map
+#[derive(Serialize, Deserialize)]
+struct LegacyAnchor {
+    #[serde(default)]
+    validations: Vec<Validation>,
+}
+
+/// State migration of the anchors, run a page at a time after `migrate`
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Migration {
+    /// last anchor migrated
+    pub start_after: Option<String>,
+    /// moves validations stored inside anchors to their own map
+    pub move_validations: bool,
+    /// saves every anchor again, adding missing secondary index entries
+    pub reindex: bool,
+    /// block time of the `migrate`, moved validations count as validated then
+    pub time: Timestamp,
+}
+
+/// Pending migration, other messages are refused until every anchor is migrated
+pub const MIGRATION: Item<Migration> = Item::new("migration");
+
+/// Migrates up to `limit` anchors after the cursor of the pending migration.
+/// Returns the number of validations moved and of anchors migrated,
+/// removing the migration once the anchors run out.
+pub fn migrate_anchors(storage: &mut dyn Storage, limit: usize) -> StdResult<(usize, usize)> {
+    let mut migration = match MIGRATION.may_load(storage)? {
+        Some(migration) => migration,
+        None => return Ok((0, 0)),
+    };
+    let legacy: Map<&str, LegacyAnchor> = Map::new("anchors");
+    let start = migration.start_after.as_deref().map(Bound::exclusive);
+    let page: Vec<(String, LegacyAnchor)> = legacy
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<_>>()?;
+
+    let mut moved = 0;
+    for (id, legacy_anchor) in &page {
+        let has_validations = migration.move_validations && !legacy_anchor.validations.is_empty();
+        if has_validations {
+            for validation in &legacy_anchor.validations {
+                add_validation(storage, id, validation)?;
+                // created is client supplied, unlike the block time of validations
+                let validated = (validation.revision, migration.time);
+                VALIDATED.save(storage, (id, &validation.source), &validated)?;
+                moved += 1;
+            }
+        }
+        if has_validations || migration.reindex {
+            // saving drops the inline validations and adds the index entries
+            let anchor = anchors().load(storage, id)?;
+            anchors().replace(storage, id, Some(&anchor), None)?;
+        }
+    }
+
+    if page.len() < limit {
+        MIGRATION.remove(storage);
+    } else {
+        migration.start_after = page.last().map(|(id, _)| id.clone());
+        MIGRATION.save(storage, &migration)?;
+    }
+    Ok((moved, page.len()))
+}
+
+/// Tally of the revision of the anchor, empty until validated
+pub fn current_tally(storage: &dyn Storage, id: &str, revision: u32) -> StdResult<Tally> {
+    let tally = TALLIES
+        .may_load(storage, id)?
+        .filter(|tally| tally.revision == revision);
+    Ok(tally.unwrap_or(Tally {
+        revision,
+        ..Default::default()
+    }))
+}
+
+/// Adds the weight of a validator to the tally of the revision of the anchor.
+/// Returns true if this crossed the quorum.
+pub fn tally_validation(
+    storage: &mut dyn Storage,
+    id: &str,
+    revision: u32,
+    weight: u64,
+    quorum: Option<u64>,
+    time: Timestamp,
+) -> StdResult<bool> {
+    let mut tally = current_tally(storage, id, revision)?;
+    let before = tally.status(quorum);
+    tally.weight += weight;
+    let crossed = before == AnchorStatus::Pending && tally.status(quorum) == AnchorStatus::Verified;
+    if crossed {
+        tally.verified = Some(time);
+    }
+    TALLIES.save(storage, id, &tally)?;
+    Ok(crossed)
 }
 
-pub const ANCHORS: Map<&str, Anchor> = Map::new("anchors");
+/// Removes the weight of an overturned validation from the tally of its revision,
+/// if still the current one
+pub fn untally_validation(
+    storage: &mut dyn Storage,
+    id: &str,
+    revision: u32,
+    weight: u64,
+) -> StdResult<()> {
+    let tally = TALLIES
+        .may_load(storage, id)?
+        .filter(|tally| tally.revision == revision);
+    if let Some(mut tally) = tally {
+        tally.weight = tally.weight.saturating_sub(weight);
+        TALLIES.save(storage, id, &tally)?;
+    }
+    Ok(())
+}
 
 /// This returns the list of ids for all active anchors
 pub fn all_anchor_ids<'a>(
@@ -29,18 +549,81 @@ pub fn all_anchor_ids<'a>(
     start: Option<Bound<'a, &'a str>>,
     limit: usize,
 ) -> StdResult<Vec<String>> {
-    ANCHORS
+    anchors()
         .keys(storage, start, None, Order::Ascending)
         .take(limit)
         .collect()
 }
 
+/// Key of the anchor in GEOHASHES, ':' is outside the geohash alphabet
+pub fn geohash_key(geohash: &str, id: &str) -> Vec<u8> {
+    format!("{}:{}", geohash, id).into_bytes()
+}
+
+/// (geohash, id) of the anchors located within the geohash prefix, in key order,
+/// after the `geohash_key` `start_after`
+pub fn anchors_in_geohash<'a>(
+    storage: &'a dyn Storage,
+    prefix: &str,
+    start_after: Option<Vec<u8>>,
+) -> impl Iterator<Item = StdResult<(String, String)>> + 'a {
+    let min = match start_after {
+        Some(key) if key.as_slice() >= prefix.as_bytes() => Bound::ExclusiveRaw(key),
+        _ => Bound::InclusiveRaw(prefix.as_bytes().to_vec()),
+    };
+    // keys are ascii, so below the prefix followed by 0xff
+    let mut max = prefix.as_bytes().to_vec();
+    max.push(0xff);
+    GEOHASHES
+        .keys(
+            storage,
+            Some(min),
+            Some(Bound::ExclusiveRaw(max)),
+            Order::Ascending,
+        )
+        .map(|key| {
+            let key = String::from_utf8(key?)?;
+            key.split_once(':')
+                .map(|(geohash, id)| (geohash.to_string(), id.to_string()))
+                .ok_or_else(|| StdError::generic_err(format!("Invalid geohash key: {}", key)))
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     use cosmwasm_std::testing::MockStorage;
-    use cosmwasm_std::Binary;
+    use cosmwasm_std::{coin, Binary};
+
+    #[test]
+    fn test_config_permissions() {
+        let config = Config {
+            admins: vec![Addr::unchecked("alice")],
+            users: vec![Addr::unchecked("ted")],
+            mutable: false,
+            revalidation_interval: None,
+            quorum: None,
+            group: None,
+            challenge_bond: None,
+            treasury: None,
+            max_batch_size: None,
+        };
+        assert!(config.is_admin("alice"));
+        assert!(!config.is_admin("ted"));
+        assert!(config.is_user("ted"));
+        assert!(config.can_execute("alice"));
+        assert!(config.can_execute("ted"));
+        assert!(!config.can_execute("mallory"));
+        assert!(!config.can_modify("alice"));
+
+        let config = Config {
+            mutable: true,
+            ..config
+        };
+        assert!(config.can_modify("alice"));
+        assert!(!config.can_modify("ted"));
+    }
 
     #[test]
     fn test_no_anchor_ids() {
@@ -54,17 +637,29 @@ mod tests {
             account: Default::default(),
             source: Addr::unchecked("source"),
             hash: Binary("hash".into()),
+            algorithm: HashAlgorithm::Sha2_256,
             created: Default::default(),
-            validations: vec![],
+            revoked: None,
+            revision: 0,
+            kind: AnchorKind::Object,
+            cid: None,
+            bounding_box: None,
+            geohash: None,
         }
     }
 
     #[test]
     fn test_all_anchor_ids() {
         let mut storage = MockStorage::new();
-        ANCHORS.save(&mut storage, "lazy", &dummy_anchor()).unwrap();
-        ANCHORS.save(&mut storage, "assign", &dummy_anchor()).unwrap();
-        ANCHORS.save(&mut storage, "zen", &dummy_anchor()).unwrap();
+        anchors()
+            .save(&mut storage, "lazy", &dummy_anchor())
+            .unwrap();
+        anchors()
+            .save(&mut storage, "assign", &dummy_anchor())
+            .unwrap();
+        anchors()
+            .save(&mut storage, "zen", &dummy_anchor())
+            .unwrap();
 
         let ids = all_anchor_ids(&storage, None, 10).unwrap();
         assert_eq!(3, ids.len());
@@ -73,4 +668,311 @@ mod tests {
             ids
         )
     }
+
+    fn start_migration(storage: &mut MockStorage, move_validations: bool, reindex: bool) {
+        let migration = Migration {
+            start_after: None,
+            move_validations,
+            reindex,
+            time: Timestamp::from_seconds(10),
+        };
+        MIGRATION.save(storage, &migration).unwrap();
+    }
+
+    #[test]
+    fn test_reindex_anchors() {
+        let mut storage = MockStorage::new();
+        // anchors saved without indexes, as before they existed
+        let legacy: Map<&str, Anchor> = Map::new("anchors");
+        legacy.save(&mut storage, "lazy", &dummy_anchor()).unwrap();
+        legacy.save(&mut storage, "zen", &dummy_anchor()).unwrap();
+        legacy.save(&mut storage, "zoo", &dummy_anchor()).unwrap();
+
+        let by_source = |storage: &MockStorage| {
+            anchors()
+                .idx
+                .source
+                .prefix(Addr::unchecked("source"))
+                .keys(storage, None, None, Order::Ascending)
+                .count()
+        };
+        assert_eq!(0, by_source(&storage));
+
+        // a page at a time
+        start_migration(&mut storage, false, true);
+        assert_eq!((0, 2), migrate_anchors(&mut storage, 2).unwrap());
+        assert_eq!(2, by_source(&storage));
+        assert_eq!(
+            Some("zen".to_string()),
+            MIGRATION.load(&storage).unwrap().start_after
+        );
+        assert_eq!((0, 1), migrate_anchors(&mut storage, 2).unwrap());
+        assert_eq!(3, by_source(&storage));
+        assert_eq!(None, MIGRATION.may_load(&storage).unwrap());
+        assert_eq!((0, 0), migrate_anchors(&mut storage, 2).unwrap());
+
+        // Running it again doesn't duplicate entries
+        start_migration(&mut storage, false, true);
+        migrate_anchors(&mut storage, 10).unwrap();
+        assert_eq!(3, by_source(&storage));
+    }
+
+    #[test]
+    fn test_validations() {
+        let mut storage = MockStorage::new();
+        let validation = Validation {
+            account: Default::default(),
+            source: Addr::unchecked("validator"),
+            hash: Binary("hash".into()),
+            algorithm: HashAlgorithm::Sha2_256,
+            created: Default::default(),
+            signature: None,
+            revision: 0,
+            weight: 0,
+        };
+        assert_eq!(0, validation_count(&storage, "lazy").unwrap());
+        assert_eq!(
+            0,
+            add_validation(&mut storage, "lazy", &validation).unwrap()
+        );
+        assert_eq!(
+            1,
+            add_validation(&mut storage, "lazy", &validation).unwrap()
+        );
+        assert_eq!(0, add_validation(&mut storage, "zen", &validation).unwrap());
+        assert_eq!(2, validation_count(&storage, "lazy").unwrap());
+        assert_eq!(1, validation_count(&storage, "zen").unwrap());
+    }
+
+    #[test]
+    fn test_rejections() {
+        let mut storage = MockStorage::new();
+        let rejection = Rejection {
+            hash: Binary("hash".into()),
+            algorithm: HashAlgorithm::Sha2_256,
+            reason: RejectionReason::Unavailable,
+            created: Default::default(),
+        };
+        let alice = Addr::unchecked("alice");
+        let bob = Addr::unchecked("bob");
+        assert_eq!(0, rejection_count(&storage, "lazy").unwrap());
+        add_rejection(&mut storage, "lazy", &alice, &rejection).unwrap();
+        add_rejection(&mut storage, "lazy", &bob, &rejection).unwrap();
+        add_rejection(&mut storage, "zen", &bob, &rejection).unwrap();
+        assert_eq!(2, rejection_count(&storage, "lazy").unwrap());
+        assert_eq!(1, rejection_count(&storage, "zen").unwrap());
+    }
+
+    #[test]
+    fn test_challenges() {
+        let mut storage = MockStorage::new();
+        let challenge = |anchor_id: &str| Challenge {
+            anchor_id: anchor_id.to_string(),
+            validation: None,
+            challenger: Addr::unchecked("insurer"),
+            bond: coin(100, "udcl"),
+            treasury: Addr::unchecked("treasury"),
+            reason: Default::default(),
+            created: Default::default(),
+            state: DisputeState::Disputed,
+            uphold_weight: 0,
+            overturn_weight: 0,
+            resolved: None,
+        };
+        assert_eq!(None, latest_challenge(&storage, "lazy").unwrap());
+        assert_eq!(0, add_challenge(&mut storage, &challenge("lazy")).unwrap());
+        assert_eq!(1, add_challenge(&mut storage, &challenge("zen")).unwrap());
+        assert_eq!(2, add_challenge(&mut storage, &challenge("lazy")).unwrap());
+        assert_eq!(
+            Some((2, challenge("lazy"))),
+            latest_challenge(&storage, "lazy").unwrap()
+        );
+        assert_eq!(1, latest_challenge(&storage, "zen").unwrap().unwrap().0);
+
+        // challenges of validations are told apart
+        let of_validation = Challenge {
+            validation: Some(4),
+            ..challenge("lazy")
+        };
+        assert_eq!(3, add_challenge(&mut storage, &of_validation).unwrap());
+        assert_eq!(3, latest_challenge(&storage, "lazy").unwrap().unwrap().0);
+        let latest = |validations| {
+            latest_challenge_against(&storage, "lazy", validations)
+                .unwrap()
+                .map(|(id, _)| id)
+        };
+        assert_eq!(Some(2), latest(false));
+        assert_eq!(Some(3), latest(true));
+        assert_eq!(
+            Some(DisputeState::Disputed),
+            validation_dispute(&storage, "lazy", 4).unwrap()
+        );
+        assert_eq!(None, validation_dispute(&storage, "lazy", 0).unwrap());
+    }
+
+    #[test]
+    fn test_migrate_validations() {
+        #[derive(Serialize, Deserialize)]
+        struct InlineAnchor {
+            account: String,
+            hash: Binary,
+            source: Addr,
+            created: Timestamp,
+            validations: Vec<Validation>,
+        }
+
+        let mut storage = MockStorage::new();
+        let validation = Validation {
+            account: Default::default(),
+            source: Addr::unchecked("validator"),
+            hash: Binary("hash".into()),
+            algorithm: HashAlgorithm::Sha2_256,
+            created: Default::default(),
+            signature: None,
+            revision: 0,
+            weight: 0,
+        };
+        let legacy: Map<&str, InlineAnchor> = Map::new("anchors");
+        let inline = |validations| InlineAnchor {
+            account: Default::default(),
+            source: Addr::unchecked("source"),
+            hash: Binary("hash".into()),
+            created: Default::default(),
+            validations,
+        };
+        legacy
+            .save(&mut storage, "lazy", &inline(vec![validation.clone(); 2]))
+            .unwrap();
+        legacy.save(&mut storage, "zen", &inline(vec![])).unwrap();
+
+        start_migration(&mut storage, true, false);
+        assert_eq!((2, 2), migrate_anchors(&mut storage, 10).unwrap());
+        assert_eq!(2, validation_count(&storage, "lazy").unwrap());
+        assert_eq!(0, validation_count(&storage, "zen").unwrap());
+        assert_eq!(
+            (0, Timestamp::from_seconds(10)),
+            VALIDATED
+                .load(&storage, ("lazy", &Addr::unchecked("validator")))
+                .unwrap()
+        );
+        assert_eq!(validation, VALIDATIONS.load(&storage, ("lazy", 1)).unwrap());
+        assert_eq!(dummy_anchor(), anchors().load(&storage, "lazy").unwrap());
+
+        // Running it again doesn't move anything
+        start_migration(&mut storage, true, false);
+        assert_eq!((0, 2), migrate_anchors(&mut storage, 10).unwrap());
+        assert_eq!(2, validation_count(&storage, "lazy").unwrap());
+    }
+
+    #[test]
+    fn test_tally_validation() {
+        let mut storage = MockStorage::new();
+        let time = Timestamp::from_seconds(10);
+        assert!(!tally_validation(&mut storage, "lazy", 0, 1, Some(2), time).unwrap());
+        assert!(tally_validation(&mut storage, "lazy", 0, 1, Some(2), time).unwrap());
+        // crossed only once
+        assert!(!tally_validation(&mut storage, "lazy", 0, 1, Some(2), time).unwrap());
+        let tally = current_tally(&storage, "lazy", 0).unwrap();
+        assert_eq!(3, tally.weight);
+        assert_eq!(AnchorStatus::Verified, tally.status(Some(2)));
+        // the status follows the quorum
+        assert_eq!(AnchorStatus::Pending, tally.status(Some(4)));
+        assert!(tally_validation(&mut storage, "lazy", 0, 1, Some(4), time).unwrap());
+
+        // a new revision starts over
+        let tally = current_tally(&storage, "lazy", 1).unwrap();
+        assert_eq!(0, tally.weight);
+        assert_eq!(AnchorStatus::Pending, tally.status(Some(2)));
+        assert!(!tally_validation(&mut storage, "lazy", 1, 1, Some(2), time).unwrap());
+        assert_eq!(1, current_tally(&storage, "lazy", 1).unwrap().weight);
+
+        // overturned validations are taken off the current revision only
+        untally_validation(&mut storage, "lazy", 0, 1).unwrap();
+        assert_eq!(1, current_tally(&storage, "lazy", 1).unwrap().weight);
+        untally_validation(&mut storage, "lazy", 1, 1).unwrap();
+        assert_eq!(0, current_tally(&storage, "lazy", 1).unwrap().weight);
+
+        // no quorum, never verified
+        assert!(!tally_validation(&mut storage, "zen", 0, 5, None, time).unwrap());
+        let tally = TALLIES.load(&storage, "zen").unwrap();
+        assert_eq!(AnchorStatus::Pending, tally.status(None));
+    }
+
+    #[test]
+    fn test_account_index() {
+        let mut storage = MockStorage::new();
+        let anchor = |account: &str| Anchor {
+            account: account.to_string(),
+            ..dummy_anchor()
+        };
+        anchors()
+            .save(&mut storage, "lazy", &anchor("acct1"))
+            .unwrap();
+        anchors()
+            .save(&mut storage, "assign", &anchor("acct2"))
+            .unwrap();
+        anchors()
+            .save(&mut storage, "zen", &anchor("acct1"))
+            .unwrap();
+
+        let ids: Vec<String> = anchors()
+            .idx
+            .account
+            .prefix("acct1".to_string())
+            .keys(&storage, None, None, Order::Ascending)
+            .collect::<StdResult<_>>()
+            .unwrap();
+        assert_eq!(vec!["lazy".to_string(), "zen".to_string()], ids);
+
+        // Re-saving under another account moves the index entry
+        anchors()
+            .save(&mut storage, "zen", &anchor("acct2"))
+            .unwrap();
+        let count = anchors()
+            .idx
+            .account
+            .prefix("acct1".to_string())
+            .keys(&storage, None, None, Order::Ascending)
+            .count();
+        assert_eq!(1, count);
+    }
+
+    #[test]
+    fn test_geohash_index() {
+        let mut storage = MockStorage::new();
+        for (geohash, id) in [
+            ("u4pr", "lazy"),
+            ("u4p", "assign"),
+            ("u4q", "zen"),
+            ("u4pr", "ace"),
+        ] {
+            GEOHASHES
+                .save(&mut storage, &geohash_key(geohash, id), &Empty {})
+                .unwrap();
+        }
+
+        let located = |prefix: &str, start_after: Option<Vec<u8>>| -> Vec<(String, String)> {
+            anchors_in_geohash(&storage, prefix, start_after)
+                .collect::<StdResult<_>>()
+                .unwrap()
+        };
+        let pair = |geohash: &str, id: &str| (geohash.to_string(), id.to_string());
+        assert_eq!(
+            located("u4p", None),
+            vec![
+                pair("u4p", "assign"),
+                pair("u4pr", "ace"),
+                pair("u4pr", "lazy")
+            ]
+        );
+        assert_eq!(located("u4pr", None).len(), 2);
+        assert_eq!(located("", None).len(), 4);
+        assert!(located("u5", None).is_empty());
+        assert_eq!(
+            located("u4p", Some(geohash_key("u4pr", "ace"))),
+            vec![pair("u4pr", "lazy")]
+        );
+        // a key before the prefix starts at the prefix
+        assert_eq!(located("u4q", Some(geohash_key("u4pr", "ace"))).len(), 1);
+    }
 }
@@ -11,14 +11,16 @@ use cosmrs::{
     AccountId, Coin,
 };
 
+use chrono::Utc;
 use cosmwasm_std::Timestamp;
 use geodata_anchor::msg::{CreateMsg, ExecuteMsg, InstantiateMsg, ValidateMsg};
+use geodata_anchor::hash::HashEncoding;
+use geodata_anchor::state::AnchorKind;
 use sha2::{Digest, Sha256};
 use std::fs::File;
 use std::io::prelude::*;
 use std::str;
 use std::str::FromStr;
-use chrono::Utc;
 use tracing::{error, info};
 
 /// Chain ID to use for tests
@@ -181,8 +183,8 @@ async fn test_workflow() {
     assert_eq!(&auth_info, &tx.auth_info);
 
     // execute/create
-    let hash = hex::encode(&Sha256::digest(
-        &hex::decode(hex::encode(b"This is a string, 32 bytes long.")).unwrap(),
+    let hash = hex::encode(Sha256::digest(
+        hex::decode(hex::encode(b"This is a string, 32 bytes long.")).unwrap(),
     ));
 
     let geodata_id = ObjectId::new().to_hex().to_string();
@@ -191,7 +193,12 @@ async fn test_workflow() {
         id: geodata_id.clone(),
         account: ObjectId::new().to_hex().to_string(),
         hash: hash.clone(),
+        encoding: HashEncoding::Hex,
         created: Timestamp::default(),
+        kind: AnchorKind::Object,
+        cid: None,
+        bounding_box: None,
+        geohash: None,
     };
 
     let create_execute_msg = ExecuteMsg::Create(create_msg);
@@ -232,7 +239,9 @@ async fn test_workflow() {
         id: geodata_id.clone(),
         account: ObjectId::new().to_hex().to_string(),
         hash: hash.clone(),
+        encoding: HashEncoding::Hex,
         created: Timestamp::from_nanos(Utc::now().timestamp_nanos() as u64),
+        signature: None,
     };
     let validate_execute_msg = ExecuteMsg::Validate(validate_msg);
     let validate_execute_msg_json = serde_json::to_string(&validate_execute_msg).unwrap();
@@ -265,5 +274,5 @@ async fn test_workflow() {
     assert_eq!(&tx_body, &tx.body);
     assert_eq!(&auth_info, &tx.auth_info);
 
-    dev::exec_docker_command("kill", &[&container_id]);
+    dev::exec_docker_command("kill", [&container_id]);
 }